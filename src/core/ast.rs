@@ -1,17 +1,118 @@
-use crate::{parenthesize, tokens::Token};
+use std::{cell::RefCell, cmp::Ordering, collections::BTreeMap, mem, rc::Rc};
+
+use crate::{builtins::NativeFunction, parenthesize, tokens::Token};
 
 #[derive(Debug, Clone)]
 pub enum Value {
     Number(f64),
+    Integer(i64),
     String(String),
     Bool(bool),
     Nil,
+    Native(Rc<NativeFunction>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Map(Rc<RefCell<BTreeMap<MapKey, Value>>>),
+    Range(Range),
+    Enum(Rc<EnumDef>),
+    Variant(Rc<EnumVariant>),
+    // variant with payload fields, accessed but not called yet
+    VariantConstructor(Rc<EnumDef>, usize),
+}
+
+// numbers of `range(start, end, step)` with the end excluded, produced one at a time
+// so a loop over a huge range doesn't allocate them all
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Range {
+    Integer { start: i64, end: i64, step: i64 },
+    Float { start: f64, end: f64, step: f64 },
+}
+
+impl Range {
+    pub fn count(&self) -> u64 {
+        match *self {
+            Range::Integer { start, end, step } => {
+                let (span, step) = (end as i128 - start as i128, step as i128);
+                if span == 0 || (span > 0) != (step > 0) {
+                    0
+                } else {
+                    ((span.abs() - 1) / step.abs() + 1) as u64
+                }
+            }
+            // computed from the count rather than by repeated addition, so a step too
+            // small to change the value can't loop forever
+            Range::Float { start, end, step } => ((end - start) / step).ceil().max(0.0) as u64,
+        }
+    }
+
+    pub fn get(&self, index: u64) -> Value {
+        match *self {
+            Range::Integer { start, step, .. } => {
+                Value::Integer((start as i128 + index as i128 * step as i128) as i64)
+            }
+            Range::Float { start, step, .. } => Value::Number(start + index as f64 * step),
+        }
+    }
+}
+
+// whole floats keep a `.0`, so they print differently from integers of the same value
+fn format_number(num: f64) -> String {
+    if num.is_finite() && num.fract() == 0.0 {
+        format!("{num:.1}")
+    } else {
+        num.to_string()
+    }
+}
+
+#[derive(Debug)]
+pub struct EnumDef {
+    pub name: String,
+    // variant names with the names of their payload fields
+    pub variants: Vec<(String, Vec<String>)>,
+}
+
+impl EnumDef {
+    pub fn variant_index(&self, name: &str) -> Option<usize> {
+        self.variants
+            .iter()
+            .position(|(variant, _)| variant == name)
+    }
 }
 
+#[derive(Debug)]
+pub struct EnumVariant {
+    pub enum_def: Rc<EnumDef>,
+    pub index: usize,
+    pub payload: Vec<Value>,
+}
+
+impl EnumVariant {
+    pub fn name(&self) -> &str {
+        &self.enum_def.variants[self.index].0
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        let fields = &self.enum_def.variants[self.index].1;
+        let position = fields.iter().position(|field| field == name)?;
+        self.payload.get(position)
+    }
+}
+
+// how deep nested collections are printed
+const MAX_FORMAT_DEPTH: usize = 256;
+
 impl Value {
+    // integers are converted, so the value can be used wherever any number fits
     pub fn as_number(&self) -> Option<f64> {
         match self {
             Self::Number(num) => Some(*num),
+            Self::Integer(int) => Some(*int as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(int) => Some(*int),
             _ => None,
         }
     }
@@ -31,7 +132,7 @@ impl Value {
     }
 
     pub fn is_number(&self) -> bool {
-        matches!(self, Self::Number(_))
+        matches!(self, Self::Number(_) | Self::Integer(_))
     }
 
     pub fn is_string(&self) -> bool {
@@ -47,15 +148,210 @@ impl Value {
     }
 
     pub fn stringify(&self) -> String {
+        self.format(false, &mut Vec::new())
+    }
+
+    // same as stringify, but strings are quoted so they are distinguishable inside collections
+    pub fn inspect(&self) -> String {
+        self.format(true, &mut Vec::new())
+    }
+
+    // lists and maps being printed are tracked, so one containing itself prints as `[...]`.
+    // so does one nested too deep, printing it would overflow the stack
+    fn format(&self, quoted: bool, visiting: &mut Vec<*const ()>) -> String {
         match self {
-            Self::Number(num) => num.to_string(),
+            Self::Number(num) => format_number(*num),
+            Self::Integer(int) => int.to_string(),
+            Self::String(str) if quoted => format!("{str:?}"),
             Self::String(str) => str.to_owned(),
             Self::Bool(val) => val.to_string(),
             Self::Nil => "nil".to_owned(),
+            Self::Native(native) => format!("<native fn {}>", native.name),
+            Self::List(list) => {
+                let pointer = Rc::as_ptr(list).cast::<()>();
+                if visiting.contains(&pointer) || visiting.len() >= MAX_FORMAT_DEPTH {
+                    return "[...]".to_owned();
+                }
+
+                visiting.push(pointer);
+                let elements: Vec<String> = list
+                    .borrow()
+                    .iter()
+                    .map(|element| element.format(true, visiting))
+                    .collect();
+                visiting.pop();
+                format!("[{}]", elements.join(", "))
+            }
+            Self::Map(map) => {
+                let pointer = Rc::as_ptr(map).cast::<()>();
+                if visiting.contains(&pointer) || visiting.len() >= MAX_FORMAT_DEPTH {
+                    return "{...}".to_owned();
+                }
+
+                visiting.push(pointer);
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        format!(
+                            "{}: {}",
+                            key.to_value().inspect(),
+                            value.format(true, visiting)
+                        )
+                    })
+                    .collect();
+                visiting.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Self::Range(Range::Integer { start, end, step }) => {
+                format!("range({}, {}, {})", start, end, step)
+            }
+            Self::Range(Range::Float { start, end, step }) => format!(
+                "range({}, {}, {})",
+                format_number(*start),
+                format_number(*end),
+                format_number(*step)
+            ),
+            Self::Enum(enum_def) => format!("<enum {}>", enum_def.name),
+            Self::Variant(variant) => {
+                let name = format!("{}.{}", variant.enum_def.name, variant.name());
+                if variant.payload.is_empty() {
+                    name
+                } else if visiting.len() >= MAX_FORMAT_DEPTH {
+                    format!("{}(...)", name)
+                } else {
+                    visiting.push(Rc::as_ptr(variant).cast::<()>());
+                    let payload: Vec<String> = variant
+                        .payload
+                        .iter()
+                        .map(|value| value.format(true, visiting))
+                        .collect();
+                    visiting.pop();
+                    format!("{}({})", name, payload.join(", "))
+                }
+            }
+            Self::VariantConstructor(enum_def, index) => {
+                format!(
+                    "<variant {}.{}>",
+                    enum_def.name, enum_def.variants[*index].0
+                )
+            }
+        }
+    }
+}
+
+// nested collections are taken apart with a loop, dropping them recursively
+// would overflow the stack when they are nested deep enough
+impl Drop for Value {
+    fn drop(&mut self) {
+        let mut pending = Vec::new();
+        self.take_elements(&mut pending);
+        // each value is left without elements, so dropping it doesn't recurse
+        while let Some(mut value) = pending.pop() {
+            value.take_elements(&mut pending);
+        }
+    }
+}
+
+impl Value {
+    // moves out elements of a collection nothing else refers to
+    fn take_elements(&mut self, pending: &mut Vec<Value>) {
+        match self {
+            Value::List(list) if Rc::strong_count(list) == 1 => {
+                pending.append(&mut list.borrow_mut());
+            }
+            Value::Map(map) if Rc::strong_count(map) == 1 => {
+                pending.extend(mem::take(&mut *map.borrow_mut()).into_values());
+            }
+            Value::Variant(variant) => {
+                if let Some(variant) = Rc::get_mut(variant) {
+                    pending.append(&mut variant.payload);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// values usable as map keys, ordered as bools, numbers and then strings
+// so maps iterate and print in a stable order
+#[derive(Debug, Clone)]
+pub enum MapKey {
+    Bool(bool),
+    Number(f64),
+    Integer(i64),
+    String(String),
+}
+
+impl MapKey {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            // whole numbers become integer keys, so `1.0` and `1` are the same key,
+            // and so are -0 and 0
+            Value::Number(num)
+                if num.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(num) =>
+            {
+                Some(Self::Integer(*num as i64))
+            }
+            Value::Number(num) => Some(Self::Number(*num)),
+            Value::Integer(int) => Some(Self::Integer(*int)),
+            Value::String(str) => Some(Self::String(str.clone())),
+            Value::Bool(val) => Some(Self::Bool(*val)),
+            _ => None,
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Self::Bool(val) => Value::Bool(*val),
+            Self::Number(num) => Value::Number(*num),
+            Self::Integer(int) => Value::Integer(*int),
+            Self::String(str) => Value::String(str.clone()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Self::Bool(_) => 0,
+            Self::Number(_) | Self::Integer(_) => 1,
+            Self::String(_) => 2,
+        }
+    }
+}
+
+impl Ord for MapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Bool(left), Self::Bool(right)) => left.cmp(right),
+            (Self::Number(left), Self::Number(right)) => left.total_cmp(right),
+            (Self::Integer(left), Self::Integer(right)) => left.cmp(right),
+            // never equal, whole numbers are always integer keys
+            (Self::Integer(left), Self::Number(right)) => {
+                (*left as f64).total_cmp(right).then(Ordering::Less)
+            }
+            (Self::Number(left), Self::Integer(right)) => {
+                left.total_cmp(&(*right as f64)).then(Ordering::Greater)
+            }
+            (Self::String(left), Self::String(right)) => left.cmp(right),
+            _ => self.rank().cmp(&other.rank()),
         }
     }
 }
 
+impl PartialOrd for MapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MapKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MapKey {}
+
 #[derive(Debug)]
 pub enum Expr {
     Binary {
@@ -67,10 +363,200 @@ pub enum Expr {
         operator: Token,
         right: Box<Expr>,
     },
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    // `left ?? right` evaluates right only when left is nil
+    Coalesce {
+        left: Box<Expr>,
+        operator: Token,
+        right: Box<Expr>,
+    },
     Grouping(Box<Expr>),
     Literal(Value),
     Variable(Token),
     Assign(Token, Box<Expr>),
+    // `a++` or `a--`, evaluates to the value before the update
+    Postfix {
+        name: Token,
+        operator: Token,
+    },
+    // `[a, b] = value` assigns every name only after the whole value is taken apart
+    AssignDestructure(Destructure, Box<Expr>),
+    Call {
+        callee: Box<Expr>,
+        paren: Token,
+        arguments: Vec<Expr>,
+    },
+    List(Vec<Expr>),
+    // parts of an interpolated string, converted to strings and joined when evaluated
+    Interpolation(Vec<Expr>),
+    Map {
+        brace: Token,
+        entries: Vec<(Expr, Expr)>,
+    },
+    Index {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+    },
+    // `Enum.Variant`, or a payload field of a variant value
+    Get {
+        object: Box<Expr>,
+        name: Token,
+    },
+    SetIndex {
+        object: Box<Expr>,
+        bracket: Token,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Match {
+        keyword: Token,
+        value: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+}
+
+#[derive(Debug)]
+pub enum Destructure {
+    Name(Token),
+    // takes elements of a list of exactly this length
+    List(Token, Vec<Destructure>),
+    // takes entries stored under the names as keys
+    Map(Token, Vec<Token>),
+}
+
+impl Destructure {
+    pub fn names(&self) -> Vec<&Token> {
+        match self {
+            Destructure::Name(name) => vec![name],
+            Destructure::List(_, elements) => elements.iter().flat_map(Self::names).collect(),
+            Destructure::Map(_, names) => names.iter().collect(),
+        }
+    }
+
+    pub fn line(&self) -> usize {
+        match self {
+            Destructure::Name(token) | Destructure::List(token, _) | Destructure::Map(token, _) => {
+                token.line
+            }
+        }
+    }
+
+    pub fn stringify(&self) -> String {
+        match self {
+            Destructure::Name(name) => name.lexeme.to_owned(),
+            Destructure::List(_, elements) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(Self::stringify)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Destructure::Map(_, names) => format!(
+                "{{{}}}",
+                names
+                    .iter()
+                    .map(|name| name.lexeme.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+#[derive(Debug)]
+pub enum Pattern {
+    // `_` matches anything without binding it
+    Wildcard,
+    Literal(Value),
+    Binding(Token),
+    // matches lists of exactly this length
+    List(Vec<Pattern>),
+    Alternatives(Vec<Pattern>),
+    // `Enum.Variant` matches any payload, `Enum.Variant(a, b)` matches it element-wise
+    Variant {
+        enum_name: Token,
+        variant: Token,
+        payload: Option<Vec<Pattern>>,
+    },
+}
+
+impl Pattern {
+    // matches every value, so arms after it can't be reached
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard | Pattern::Binding(_) => true,
+            Pattern::Literal(_) | Pattern::List(_) | Pattern::Variant { .. } => false,
+            Pattern::Alternatives(alternatives) => alternatives.iter().any(Self::is_irrefutable),
+        }
+    }
+
+    // names bound when the pattern matches
+    pub fn names(&self) -> Vec<&Token> {
+        match self {
+            Pattern::Wildcard | Pattern::Literal(_) => Vec::new(),
+            Pattern::Binding(name) => vec![name],
+            Pattern::List(elements) => elements.iter().flat_map(Self::names).collect(),
+            Pattern::Alternatives(alternatives) => {
+                alternatives.iter().flat_map(Self::names).collect()
+            }
+            Pattern::Variant { payload, .. } => {
+                payload.iter().flatten().flat_map(Self::names).collect()
+            }
+        }
+    }
+
+    pub fn stringify(&self) -> String {
+        match self {
+            Pattern::Wildcard => "_".to_owned(),
+            Pattern::Literal(value) => value.inspect(),
+            Pattern::Binding(name) => name.lexeme.to_owned(),
+            Pattern::List(elements) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(Self::stringify)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Pattern::Alternatives(alternatives) => alternatives
+                .iter()
+                .map(Self::stringify)
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Pattern::Variant {
+                enum_name,
+                variant,
+                payload,
+            } => {
+                let name = format!("{}.{}", enum_name.lexeme, variant.lexeme);
+                match payload {
+                    Some(payload) => format!(
+                        "{}({})",
+                        name,
+                        payload
+                            .iter()
+                            .map(Self::stringify)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    None => name,
+                }
+            }
+        }
+    }
 }
 
 impl Expr {
@@ -82,7 +568,8 @@ impl Expr {
                 right,
             } => parenthesize!(operator.lexeme, left.stringify(), right.stringify()),
             Expr::Literal(literal) => match literal {
-                Value::Number(num) => num.to_string(),
+                Value::Number(num) => format_number(*num),
+                Value::Integer(int) => int.to_string(),
                 Value::String(str) => format!("\"{}\"", str.to_owned()),
                 Value::Bool(value) => {
                     if *value {
@@ -92,11 +579,130 @@ impl Expr {
                     }
                 }
                 Value::Nil => "nil".to_owned(),
+                Value::Native(_)
+                | Value::List(_)
+                | Value::Map(_)
+                | Value::Range(_)
+                | Value::Enum(_)
+                | Value::Variant(_)
+                | Value::VariantConstructor(..) => literal.stringify(),
             },
             Expr::Unary { operator, right } => parenthesize!(operator.lexeme, right.stringify()),
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => parenthesize!(
+                "?:",
+                condition.stringify(),
+                then_branch.stringify(),
+                else_branch.stringify()
+            ),
+            Expr::Coalesce { left, right, .. } => {
+                parenthesize!("??", left.stringify(), right.stringify())
+            }
             Expr::Grouping(expr) => parenthesize!("group", expr.stringify()),
             Expr::Variable(name) => name.lexeme.to_owned(),
             Expr::Assign(name, expr) => parenthesize!("assign", name.lexeme, expr.stringify()),
+            Expr::Postfix { name, operator } => {
+                parenthesize!("postfix", operator.lexeme, name.lexeme)
+            }
+            Expr::AssignDestructure(target, expr) => {
+                parenthesize!("assign", target.stringify(), expr.stringify())
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                let mut builder = format!("(call {}", callee.stringify());
+                for argument in arguments {
+                    builder.push(' ');
+                    builder.push_str(&argument.stringify());
+                }
+                builder.push(')');
+                builder
+            }
+            Expr::List(elements) => {
+                let mut builder = "(list".to_owned();
+                for element in elements {
+                    builder.push(' ');
+                    builder.push_str(&element.stringify());
+                }
+                builder.push(')');
+                builder
+            }
+            Expr::Interpolation(parts) => {
+                let mut builder = "(interpolate".to_owned();
+                for part in parts {
+                    builder.push(' ');
+                    builder.push_str(&part.stringify());
+                }
+                builder.push(')');
+                builder
+            }
+            Expr::Map { entries, .. } => {
+                let mut builder = "(map".to_owned();
+                for (key, value) in entries {
+                    builder.push(' ');
+                    builder.push_str(&parenthesize!("entry", key.stringify(), value.stringify()));
+                }
+                builder.push(')');
+                builder
+            }
+            Expr::Index { object, index, .. } => {
+                parenthesize!("index", object.stringify(), index.stringify())
+            }
+            Expr::Get { object, name } => parenthesize!("get", object.stringify(), name.lexeme),
+            Expr::SetIndex {
+                object,
+                index,
+                value,
+                ..
+            } => parenthesize!(
+                "set-index",
+                object.stringify(),
+                index.stringify(),
+                value.stringify()
+            ),
+            Expr::Match { value, arms, .. } => {
+                let mut builder = format!("(match {}", value.stringify());
+                for arm in arms {
+                    builder.push(' ');
+                    builder.push_str(&match &arm.guard {
+                        Some(guard) => parenthesize!(
+                            "arm",
+                            arm.pattern.stringify(),
+                            guard.stringify(),
+                            arm.body.stringify()
+                        ),
+                        None => {
+                            parenthesize!("arm", arm.pattern.stringify(), arm.body.stringify())
+                        }
+                    });
+                }
+                builder.push(')');
+                builder
+            }
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Expr::Binary { operator, .. } | Expr::Unary { operator, .. } => Some(operator.line),
+            Expr::Coalesce { operator, .. } => Some(operator.line),
+            Expr::Conditional { condition, .. } => condition.line(),
+            Expr::Grouping(expr) => expr.line(),
+            Expr::Literal(_) => None,
+            Expr::Variable(name) | Expr::Assign(name, _) => Some(name.line),
+            Expr::AssignDestructure(target, _) => Some(target.line()),
+            Expr::Postfix { name, .. } => Some(name.line),
+            Expr::Call { paren, .. } => Some(paren.line),
+            Expr::List(elements) | Expr::Interpolation(elements) => {
+                elements.iter().find_map(Expr::line)
+            }
+            Expr::Map { brace, .. } => Some(brace.line),
+            Expr::Get { name, .. } => Some(name.line),
+            Expr::Match { keyword, .. } => Some(keyword.line),
+            Expr::Index { bracket, .. } | Expr::SetIndex { bracket, .. } => Some(bracket.line),
         }
     }
 }
@@ -104,6 +710,47 @@ impl Expr {
 pub enum Stmt {
     Block(Box<Vec<Stmt>>),
     Expression(Expr),
-    Print(Expr),
+    Print(Token, Expr),
     Var(Token, Option<Expr>),
+    VarDestructure(Destructure, Expr),
+    // enum name with its variants and their payload field names
+    Enum(Token, Vec<(Token, Vec<Token>)>),
+    Const(Token, Expr),
+    Break(Token),
+    Continue(Token),
+    Throw(Token, Expr),
+    ForIn {
+        keyword: Token,
+        name: Token,
+        iterable: Expr,
+        body: Box<Stmt>,
+    },
+    Try {
+        body: Vec<Stmt>,
+        // name the caught value is bound to, and the handler
+        catch: Option<(Token, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
+    },
+    Import {
+        keyword: Token,
+        path: String,
+        // only these names are imported, all top-level ones otherwise
+        names: Option<Vec<Token>>,
+    },
+}
+
+impl Stmt {
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Stmt::Block(_) | Stmt::Try { .. } => None,
+            Stmt::Expression(expr) => expr.line(),
+            Stmt::Print(keyword, _) => Some(keyword.line),
+            Stmt::Var(name, _) | Stmt::Const(name, _) | Stmt::Enum(name, _) => Some(name.line),
+            Stmt::VarDestructure(target, _) => Some(target.line()),
+            Stmt::Break(keyword) | Stmt::Continue(keyword) | Stmt::Throw(keyword, _) => {
+                Some(keyword.line)
+            }
+            Stmt::ForIn { keyword, .. } | Stmt::Import { keyword, .. } => Some(keyword.line),
+        }
+    }
 }
@@ -0,0 +1,60 @@
+use std::{fs, io};
+
+use crate::ast::Value;
+
+use super::{Capability, NativeFunction, NativeResult};
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "readLine",
+            arity: 0,
+            capability: Capability::IoRead,
+            function: read_line,
+        },
+        NativeFunction {
+            name: "readFile",
+            arity: 1,
+            capability: Capability::IoRead,
+            function: read_file,
+        },
+        NativeFunction {
+            name: "writeFile",
+            arity: 2,
+            capability: Capability::IoWrite,
+            function: write_file,
+        },
+    ]
+}
+
+// reads a line from stdin without trailing newline, nil at the end of input
+fn read_line(_: &[Value]) -> NativeResult {
+    let mut line = String::new();
+    match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(Value::Nil),
+        Ok(_) => Ok(Value::String(
+            line.trim_end_matches(['\n', '\r']).to_owned(),
+        )),
+        Err(error) => Err(format!("Error reading input: {error}.").into()),
+    }
+}
+
+fn read_file(args: &[Value]) -> NativeResult {
+    let path = expect_path(&args[0])?;
+    fs::read_to_string(&path)
+        .map(Value::String)
+        .map_err(|error| format!("Error reading '{path}': {error}.").into())
+}
+
+fn write_file(args: &[Value]) -> NativeResult {
+    let path = expect_path(&args[0])?;
+    fs::write(&path, args[1].stringify())
+        .map(|_| Value::Nil)
+        .map_err(|error| format!("Error writing '{path}': {error}.").into())
+}
+
+fn expect_path(value: &Value) -> Result<String, String> {
+    value
+        .as_string()
+        .ok_or_else(|| "Path must be a string.".to_owned())
+}
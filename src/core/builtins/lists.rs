@@ -0,0 +1,175 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    ast::{Range, Value},
+    interpreter::is_equal,
+};
+
+use super::{Capability, NativeFunction, NativeResult};
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "len",
+            arity: 1,
+            capability: Capability::Core,
+            function: len,
+        },
+        NativeFunction {
+            name: "push",
+            arity: 2,
+            capability: Capability::Core,
+            function: push,
+        },
+        NativeFunction {
+            name: "pop",
+            arity: 1,
+            capability: Capability::Core,
+            function: pop,
+        },
+        NativeFunction {
+            name: "insert",
+            arity: 3,
+            capability: Capability::Core,
+            function: insert,
+        },
+        NativeFunction {
+            name: "remove",
+            arity: 2,
+            capability: Capability::Core,
+            function: remove,
+        },
+        NativeFunction {
+            name: "slice",
+            arity: 3,
+            capability: Capability::Core,
+            function: slice,
+        },
+        NativeFunction {
+            name: "contains",
+            arity: 2,
+            capability: Capability::Core,
+            function: contains,
+        },
+        NativeFunction {
+            name: "range",
+            arity: 3,
+            capability: Capability::Core,
+            function: range,
+        },
+    ]
+}
+
+// converts an integer into a position within 0..=max, so the same check fits
+// both element access (max = len - 1) and insertion or slicing (max = len)
+pub fn to_index(value: &Value, max: usize) -> Result<usize, String> {
+    let Value::Integer(index) = *value else {
+        return Err("Index must be an integer.".to_owned());
+    };
+
+    usize::try_from(index)
+        .ok()
+        .filter(|index| *index <= max)
+        .ok_or_else(|| format!("Index {} is out of bounds.", index))
+}
+
+fn expect_list(value: &Value) -> Result<Rc<RefCell<Vec<Value>>>, String> {
+    match value {
+        Value::List(list) => Ok(Rc::clone(list)),
+        _ => Err("Argument must be a list.".into()),
+    }
+}
+
+fn len(args: &[Value]) -> NativeResult {
+    match &args[0] {
+        Value::List(list) => Ok(Value::Integer(list.borrow().len() as i64)),
+        Value::Map(map) => Ok(Value::Integer(map.borrow().len() as i64)),
+        Value::String(str) => Ok(Value::Integer(str.chars().count() as i64)),
+        Value::Range(range) => i64::try_from(range.count())
+            .map(Value::Integer)
+            .map_err(|_| "Range is too long.".into()),
+        _ => Err("Argument must be a list, map, string or range.".into()),
+    }
+}
+
+fn push(args: &[Value]) -> NativeResult {
+    expect_list(&args[0])?.borrow_mut().push(args[1].clone());
+    Ok(Value::Nil)
+}
+
+fn pop(args: &[Value]) -> NativeResult {
+    expect_list(&args[0])?
+        .borrow_mut()
+        .pop()
+        .ok_or_else(|| "Can't pop from an empty list.".into())
+}
+
+fn insert(args: &[Value]) -> NativeResult {
+    let list = expect_list(&args[0])?;
+    let mut list = list.borrow_mut();
+    let index = to_index(&args[1], list.len())?;
+    list.insert(index, args[2].clone());
+    Ok(Value::Nil)
+}
+
+fn remove(args: &[Value]) -> NativeResult {
+    let list = expect_list(&args[0])?;
+    let mut list = list.borrow_mut();
+    if list.is_empty() {
+        return Err("Can't remove from an empty list.".into());
+    }
+    let index = to_index(&args[1], list.len() - 1)?;
+    Ok(list.remove(index))
+}
+
+// elements from start up to, but not including, end as a new list
+fn slice(args: &[Value]) -> NativeResult {
+    let list = expect_list(&args[0])?;
+    let list = list.borrow();
+    let start = to_index(&args[1], list.len())?;
+    let end = to_index(&args[2], list.len())?;
+    if start > end {
+        return Err("Slice start must not be greater than end.".into());
+    }
+    Ok(Value::List(Rc::new(RefCell::new(
+        list[start..end].to_vec(),
+    ))))
+}
+
+fn contains(args: &[Value]) -> NativeResult {
+    let list = expect_list(&args[0])?;
+    let found = list
+        .borrow()
+        .iter()
+        .any(|element| is_equal(element, &args[1]));
+    Ok(Value::Bool(found))
+}
+
+fn range(args: &[Value]) -> NativeResult {
+    if let (Some(start), Some(end), Some(step)) = (
+        args[0].as_integer(),
+        args[1].as_integer(),
+        args[2].as_integer(),
+    ) {
+        if step == 0 {
+            return Err("Step must not be zero.".into());
+        }
+        return Ok(Value::Range(Range::Integer { start, end, step }));
+    }
+
+    let (Some(start), Some(end), Some(step)) = (
+        args[0].as_number(),
+        args[1].as_number(),
+        args[2].as_number(),
+    ) else {
+        return Err("Arguments must be numbers.".into());
+    };
+    if step == 0.0 {
+        return Err("Step must not be zero.".into());
+    }
+    if !((end - start) / step).is_finite() {
+        return Err("Range must be finite.".into());
+    }
+
+    Ok(Value::Range(Range::Float { start, end, step }))
+}
@@ -0,0 +1,78 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::ast::{MapKey, Value};
+
+use super::{Capability, NativeFunction, NativeResult};
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "keys",
+            arity: 1,
+            capability: Capability::Core,
+            function: keys,
+        },
+        NativeFunction {
+            name: "values",
+            arity: 1,
+            capability: Capability::Core,
+            function: values,
+        },
+        NativeFunction {
+            name: "has",
+            arity: 2,
+            capability: Capability::Core,
+            function: has,
+        },
+        NativeFunction {
+            name: "delete",
+            arity: 2,
+            capability: Capability::Core,
+            function: delete,
+        },
+    ]
+}
+
+type Map = Rc<RefCell<BTreeMap<MapKey, Value>>>;
+
+fn expect_map(value: &Value) -> Result<Map, String> {
+    match value {
+        Value::Map(map) => Ok(Rc::clone(map)),
+        _ => Err("Argument must be a map.".into()),
+    }
+}
+
+fn expect_key(value: &Value) -> Result<MapKey, String> {
+    MapKey::from_value(value).ok_or_else(|| "Map key must be a string, number or bool.".to_owned())
+}
+
+fn new_list(values: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(values)))
+}
+
+fn keys(args: &[Value]) -> NativeResult {
+    let map = expect_map(&args[0])?;
+    let keys = map.borrow().keys().map(MapKey::to_value).collect();
+    Ok(new_list(keys))
+}
+
+fn values(args: &[Value]) -> NativeResult {
+    let map = expect_map(&args[0])?;
+    let values = map.borrow().values().cloned().collect();
+    Ok(new_list(values))
+}
+
+fn has(args: &[Value]) -> NativeResult {
+    let map = expect_map(&args[0])?;
+    let key = expect_key(&args[1])?;
+    let found = map.borrow().contains_key(&key);
+    Ok(Value::Bool(found))
+}
+
+// removes the key and returns its value, nil when there was no such key
+fn delete(args: &[Value]) -> NativeResult {
+    let map = expect_map(&args[0])?;
+    let key = expect_key(&args[1])?;
+    let removed = map.borrow_mut().remove(&key);
+    Ok(removed.unwrap_or(Value::Nil))
+}
@@ -0,0 +1,98 @@
+mod io;
+pub mod lists;
+mod maps;
+mod system;
+mod values;
+
+use std::{fmt, rc::Rc};
+
+use super::{ast::Value, environment::Environment};
+
+// named sets of builtins a host may allow the script to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    Core,
+    IoRead,
+    IoWrite,
+    Env,
+    Process,
+    Time,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 6] = [
+        Capability::Core,
+        Capability::IoRead,
+        Capability::IoWrite,
+        Capability::Env,
+        Capability::Process,
+        Capability::Time,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Capability::Core => "core",
+            Capability::IoRead => "io.read",
+            Capability::IoWrite => "io.write",
+            Capability::Env => "env",
+            Capability::Process => "process",
+            Capability::Time => "time",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|capability| capability.name() == name)
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+pub type NativeResult = Result<Value, NativeError>;
+
+pub enum NativeError {
+    // message of a failed call, interpreter attaches the call site to it
+    Message(String),
+    // `exit(code)`, unwinds the script so the host decides how to stop
+    Exit(i32),
+}
+
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        NativeError::Message(message)
+    }
+}
+
+impl From<&str> for NativeError {
+    fn from(message: &str) -> Self {
+        NativeError::Message(message.to_owned())
+    }
+}
+
+#[derive(Debug)]
+pub struct NativeFunction {
+    pub name: &'static str,
+    pub arity: usize,
+    pub capability: Capability,
+    pub function: fn(&[Value]) -> NativeResult,
+}
+
+// every builtin is defined regardless of allowed capabilities,
+// so calling a denied one reports the missing capability instead of undefined variable
+pub fn define_globals(environment: &Environment) {
+    let natives = values::natives()
+        .into_iter()
+        .chain(lists::natives())
+        .chain(maps::natives())
+        .chain(io::natives())
+        .chain(system::natives());
+
+    for native in natives {
+        environment.define(native.name.to_owned(), Value::Native(Rc::new(native)));
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    env,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::ast::Value;
+
+use super::{Capability, NativeError, NativeFunction, NativeResult};
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "getEnv",
+            arity: 1,
+            capability: Capability::Env,
+            function: get_env,
+        },
+        NativeFunction {
+            name: "exit",
+            arity: 1,
+            capability: Capability::Process,
+            function: exit,
+        },
+        NativeFunction {
+            name: "clock",
+            arity: 0,
+            capability: Capability::Time,
+            function: clock,
+        },
+    ]
+}
+
+// value of environment variable, nil when it's not set
+fn get_env(args: &[Value]) -> NativeResult {
+    let name = args[0]
+        .as_string()
+        .ok_or_else(|| "Variable name must be a string.".to_owned())?;
+    Ok(env::var(name).map_or(Value::Nil, Value::String))
+}
+
+fn exit(args: &[Value]) -> NativeResult {
+    let code = args[0]
+        .as_number()
+        .ok_or_else(|| "Exit code must be a number.".to_owned())?;
+    Err(NativeError::Exit(code as i32))
+}
+
+// seconds since unix epoch
+fn clock(_: &[Value]) -> NativeResult {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|error| error.to_string())?;
+    Ok(Value::Number(elapsed.as_secs_f64()))
+}
@@ -0,0 +1,123 @@
+use crate::ast::Value;
+
+use super::{Capability, NativeFunction, NativeResult};
+
+pub fn natives() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction {
+            name: "type",
+            arity: 1,
+            capability: Capability::Core,
+            function: type_of,
+        },
+        NativeFunction {
+            name: "str",
+            arity: 1,
+            capability: Capability::Core,
+            function: str,
+        },
+        NativeFunction {
+            name: "num",
+            arity: 1,
+            capability: Capability::Core,
+            function: num,
+        },
+        NativeFunction {
+            name: "int",
+            arity: 1,
+            capability: Capability::Core,
+            function: int,
+        },
+        NativeFunction {
+            name: "float",
+            arity: 1,
+            capability: Capability::Core,
+            function: float,
+        },
+        NativeFunction {
+            name: "div",
+            arity: 2,
+            capability: Capability::Core,
+            function: div,
+        },
+    ]
+}
+
+fn type_of(args: &[Value]) -> NativeResult {
+    let name = match &args[0] {
+        Value::Number(_) => "number",
+        Value::Integer(_) => "integer",
+        Value::String(_) => "string",
+        Value::Bool(_) => "bool",
+        Value::Nil => "nil",
+        Value::Native(_) | Value::VariantConstructor(..) => "function",
+        Value::List(_) => "list",
+        Value::Map(_) => "map",
+        Value::Range(_) => "range",
+        Value::Enum(_) => "enum",
+        Value::Variant(_) => "variant",
+    };
+    Ok(Value::String(name.to_owned()))
+}
+
+fn str(args: &[Value]) -> NativeResult {
+    Ok(Value::String(args[0].stringify()))
+}
+
+// parses a string into a number, nil when it's not a valid number
+fn num(args: &[Value]) -> NativeResult {
+    match &args[0] {
+        Value::Number(_) | Value::Integer(_) => Ok(args[0].clone()),
+        Value::String(str) => Ok(str.trim().parse().map_or(Value::Nil, Value::Number)),
+        _ => Err("Argument must be a string or a number.".into()),
+    }
+}
+
+// truncates a number towards zero, or parses a string, nil when it's not a valid integer
+fn int(args: &[Value]) -> NativeResult {
+    match &args[0] {
+        Value::Integer(int) => Ok(Value::Integer(*int)),
+        Value::Number(num) => {
+            let truncated = num.trunc();
+            if !(i64::MIN as f64..i64::MAX as f64).contains(&truncated) {
+                return Err(format!("Number {} doesn't fit into an integer.", num).into());
+            }
+            Ok(Value::Integer(truncated as i64))
+        }
+        Value::String(str) => Ok(str.trim().parse().map_or(Value::Nil, Value::Integer)),
+        _ => Err("Argument must be a string or a number.".into()),
+    }
+}
+
+// converts to a float number, or parses a string, nil when it's not a valid number
+fn float(args: &[Value]) -> NativeResult {
+    match &args[0] {
+        Value::String(str) => Ok(str.trim().parse().map_or(Value::Nil, Value::Number)),
+        value => value
+            .as_number()
+            .map(Value::Number)
+            .ok_or_else(|| "Argument must be a string or a number.".into()),
+    }
+}
+
+// division rounded down, which stays an integer for integer arguments
+fn div(args: &[Value]) -> NativeResult {
+    match (&args[0], &args[1]) {
+        (Value::Integer(_), Value::Integer(0)) => Err("Division by zero.".into()),
+        (Value::Integer(left), Value::Integer(right)) => {
+            let quotient = left
+                .checked_div(*right)
+                .ok_or_else(|| "Integer overflow.".to_owned())?;
+            // truncated quotient is one above the floor when the signs differ
+            if left % right != 0 && (*left < 0) != (*right < 0) {
+                Ok(Value::Integer(quotient - 1))
+            } else {
+                Ok(Value::Integer(quotient))
+            }
+        }
+        (left, right) => match (left.as_number(), right.as_number()) {
+            (Some(left), Some(right)) => Ok(Value::Number((left / right).floor())),
+            _ => Err("Arguments must be numbers.".into()),
+        },
+    }
+}
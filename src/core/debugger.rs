@@ -0,0 +1,19 @@
+use std::{path::Path, rc::Rc};
+
+use super::environment::Environment;
+
+pub trait DebugHook {
+    // called right before a statement that maps to a source line is executed,
+    // the hook may block here to pause the program. script path is canonical,
+    // and points to the imported module while one is running. import depth
+    // grows by one for each module being imported at the moment
+    fn on_statement(
+        &mut self,
+        script_path: Option<&Path>,
+        import_depth: usize,
+        line: usize,
+        environment: &Rc<Environment>,
+    );
+    // receives text of `print` statements instead of stdout
+    fn on_output(&mut self, text: &str);
+}
@@ -1,6 +1,6 @@
 use std::{
     cell::RefCell,
-    collections::{hash_map::Entry, HashMap},
+    collections::{HashMap, HashSet},
     rc::Rc,
 };
 
@@ -8,12 +8,15 @@ use super::{ast::Value, tokens::Token};
 
 pub enum EnvError {
     AssignUndefinedVariable,
+    AssignConstant,
 }
 
 #[derive(Debug, Default)]
 pub struct Environment {
     enclosing: Option<Rc<Environment>>,
     values: RefCell<HashMap<String, Value>>,
+    // names of bindings in this scope which can't be assigned
+    constants: RefCell<HashSet<String>>,
 }
 
 impl Environment {
@@ -24,6 +27,21 @@ impl Environment {
         }
     }
 
+    pub fn enclosing(&self) -> Option<&Rc<Environment>> {
+        self.enclosing.as_ref()
+    }
+
+    pub fn bindings(&self) -> Vec<(String, Value)> {
+        let mut bindings: Vec<(String, Value)> = self
+            .values
+            .borrow()
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
     pub fn get(&self, name: &Token) -> Option<Value> {
         let values = self.values.borrow_mut();
         if let Some(value) = values.get(&name.lexeme) {
@@ -35,16 +53,38 @@ impl Environment {
         }
     }
 
+    // looks up a name in this scope only, ignoring enclosing ones
+    pub fn get_local(&self, name: &str) -> Option<Value> {
+        self.values.borrow().get(name).cloned()
+    }
+
     pub fn define(&self, name: String, value: Value) {
+        self.constants.borrow_mut().remove(&name);
         let mut values = self.values.borrow_mut();
         values.insert(name, value);
     }
 
+    pub fn define_constant(&self, name: String, value: Value) {
+        self.constants.borrow_mut().insert(name.clone());
+        let mut values = self.values.borrow_mut();
+        values.insert(name, value);
+    }
+
+    pub fn is_local_constant(&self, name: &str) -> bool {
+        self.constants.borrow().contains(name)
+    }
+
     pub fn assign(&self, name: String, value: Value) -> Result<(), EnvError> {
+        if self.is_local_constant(&name) {
+            return Err(EnvError::AssignConstant);
+        }
+
         let mut values = self.values.borrow_mut();
-        if let Entry::Occupied(mut entry) = values.entry(name) {
-            entry.insert(value);
+        if let Some(entry) = values.get_mut(&name) {
+            *entry = value;
             Ok(())
+        } else if let Some(enclosing) = &self.enclosing {
+            enclosing.assign(name, value)
         } else {
             Err(EnvError::AssignUndefinedVariable)
         }
@@ -1,9 +1,22 @@
-use std::rc::Rc;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs, mem,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Instant,
+};
 
 use super::{
-    ast::{Expr, Stmt, Value},
-    environment::Environment,
-    reporter::ErrorReporter,
+    ast::{Destructure, EnumDef, EnumVariant, Expr, MapKey, MatchArm, Pattern, Stmt, Value},
+    builtins::{self, lists, Capability, NativeError, NativeFunction},
+    cancellation::CancellationToken,
+    debugger::DebugHook,
+    environment::{EnvError, Environment},
+    limits::Limits,
+    parser::Parser,
+    reporter::{module_reporter::ModuleReporter, ErrorReporter},
+    scanner::Scanner,
     tokens::{Token, TokenType},
 };
 
@@ -13,6 +26,159 @@ pub enum RuntimeError {
     OperandsMustBeStrings(Token),
     UndefinedVariable(Token),
     AssignUndefinedVariable(Token),
+    AssignConstant(Token),
+    RedeclareConstant(Token),
+    OperationLimitExceeded(usize),
+    DepthLimitExceeded(usize),
+    DeadlineExceeded(usize),
+    Cancelled(usize),
+    NotCallable(Token),
+    ArityMismatch(Token, usize, usize),
+    CapabilityDenied(Token, &'static str, Capability),
+    NativeError(Token, String),
+    ImportError(Token, String),
+    NotIndexable(Token),
+    InvalidMapKey(Token),
+    IndexOutOfBounds(Token, String),
+    NotIterable(Token),
+    NoMatchingArm(Token, Value),
+    PayloadMismatch(Token, usize, usize),
+    DestructureMismatch(Token, String),
+    UndefinedVariant(Token, String),
+    IntegerOverflow(Token),
+    DivisionByZero(Token),
+    UndefinedProperty(Token),
+    NoProperties(Token),
+    // property of a variant constructor, the variant wasn't called yet
+    VariantNotCalled(Token, String),
+    // value of `throw` statement
+    Thrown(Token, Value),
+    // `exit(code)` stops the script, the host ends the process with the code
+    Exit(Token, i32),
+    // error raised while running an imported module, with the module path
+    InModule(String, Box<RuntimeError>),
+    // not errors, but unwinding to the enclosing loop
+    Break(Token),
+    Continue(Token),
+}
+
+impl RuntimeError {
+    pub fn line(&self) -> usize {
+        match self {
+            RE::OperationLimitExceeded(line)
+            | RE::DepthLimitExceeded(line)
+            | RE::DeadlineExceeded(line)
+            | RE::Cancelled(line) => *line,
+            RE::InModule(_, error) => error.line(),
+            RE::OperandMustBeNumber(token)
+            | RE::OperandsMustBeNumbers(token)
+            | RE::OperandsMustBeStrings(token)
+            | RE::UndefinedVariable(token)
+            | RE::AssignUndefinedVariable(token)
+            | RE::AssignConstant(token)
+            | RE::RedeclareConstant(token)
+            | RE::NotCallable(token)
+            | RE::ArityMismatch(token, ..)
+            | RE::CapabilityDenied(token, ..)
+            | RE::NativeError(token, _)
+            | RE::ImportError(token, _)
+            | RE::NotIndexable(token)
+            | RE::InvalidMapKey(token)
+            | RE::IndexOutOfBounds(token, _)
+            | RE::NotIterable(token)
+            | RE::NoMatchingArm(token, _)
+            | RE::PayloadMismatch(token, ..)
+            | RE::DestructureMismatch(token, _)
+            | RE::UndefinedVariant(token, _)
+            | RE::IntegerOverflow(token)
+            | RE::DivisionByZero(token)
+            | RE::UndefinedProperty(token)
+            | RE::NoProperties(token)
+            | RE::VariantNotCalled(token, _)
+            | RE::Thrown(token, _)
+            | RE::Exit(token, _)
+            | RE::Break(token)
+            | RE::Continue(token) => token.line,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            RE::OperandMustBeNumber(_) => "Operand must be a number.".to_owned(),
+            RE::OperandsMustBeNumbers(_) => "Operands must be numbers.".to_owned(),
+            RE::OperandsMustBeStrings(_) => "Operands must be strings.".to_owned(),
+            RE::UndefinedVariable(name) => format!("Undefined variable '{}'.", name.lexeme),
+            RE::AssignUndefinedVariable(name) => {
+                format!("Assign undefined variable '{}'.", name.lexeme)
+            }
+            RE::AssignConstant(name) => format!("Can't assign to constant '{}'.", name.lexeme),
+            RE::RedeclareConstant(name) => {
+                format!("Already a constant named '{}' in this scope.", name.lexeme)
+            }
+            RE::OperationLimitExceeded(_) => "Operation limit exceeded.".to_owned(),
+            RE::DepthLimitExceeded(_) => "Maximum nesting depth exceeded.".to_owned(),
+            RE::DeadlineExceeded(_) => "Execution time limit exceeded.".to_owned(),
+            RE::Cancelled(_) => "Execution cancelled.".to_owned(),
+            RE::NotCallable(_) => "Can only call functions and classes.".to_owned(),
+            RE::ArityMismatch(_, expected, got) => {
+                format!("Expected {} arguments but got {}.", expected, got)
+            }
+            RE::CapabilityDenied(_, name, capability) => format!(
+                "'{}' requires capability '{}' which is not allowed.",
+                name, capability
+            ),
+            RE::NativeError(_, message)
+            | RE::ImportError(_, message)
+            | RE::IndexOutOfBounds(_, message)
+            | RE::DestructureMismatch(_, message) => message.to_owned(),
+            RE::NotIndexable(_) => "Only lists and maps can be indexed.".to_owned(),
+            RE::InvalidMapKey(_) => "Map key must be a string, number or bool.".to_owned(),
+            RE::NotIterable(_) => {
+                "Can only iterate over lists, maps, strings and ranges.".to_owned()
+            }
+            RE::UndefinedVariant(name, enum_name) => {
+                format!("Enum '{}' has no variant '{}'.", enum_name, name.lexeme)
+            }
+            RE::IntegerOverflow(_) => "Integer overflow.".to_owned(),
+            RE::DivisionByZero(_) => "Division by zero.".to_owned(),
+            RE::UndefinedProperty(name) => format!("Undefined property '{}'.", name.lexeme),
+            RE::NoProperties(_) => "Only enums and variants have properties.".to_owned(),
+            RE::VariantNotCalled(_, variant) => format!(
+                "Variant '{}' has fields, call it before reading them.",
+                variant
+            ),
+            RE::NoMatchingArm(_, value) => format!("No match arm for {}.", value.inspect()),
+            RE::PayloadMismatch(variant, fields, patterns) => format!(
+                "Variant '{}' has {} fields but the pattern has {}.",
+                variant.lexeme, fields, patterns
+            ),
+            RE::Thrown(_, value) => format!("Uncaught exception: {}", value.stringify()),
+            RE::InModule(_, error) => error.message(),
+            // stops the script instead of being reported
+            RE::Exit(_, code) => format!("Exited with code {}.", code),
+            // parser rejects them outside of loops, so they are never reported
+            RE::Break(keyword) | RE::Continue(keyword) => {
+                format!("Can't use '{}' outside of a loop.", keyword.lexeme)
+            }
+        }
+    }
+
+    // limits, cancellation and loop jumps have to unwind the whole script, `catch` skips them
+    pub fn is_catchable(&self) -> bool {
+        match self {
+            RE::InModule(_, error) => error.is_catchable(),
+            _ => !matches!(
+                self,
+                RE::OperationLimitExceeded(_)
+                    | RE::DepthLimitExceeded(_)
+                    | RE::DeadlineExceeded(_)
+                    | RE::Cancelled(_)
+                    | RE::Exit(..)
+                    | RE::Break(_)
+                    | RE::Continue(_)
+            ),
+        }
+    }
 }
 
 use RuntimeError as RE;
@@ -22,12 +188,38 @@ use Value as V;
 #[derive(Default)]
 pub struct Interpreter {
     environment: Rc<Environment>,
+    // natives live in their own scope enclosing top-level scope of every module
+    builtins: Rc<Environment>,
     reporter: Option<Rc<dyn ErrorReporter>>,
+    debugger: Option<Box<dyn DebugHook>>,
+    limits: Limits,
+    cancellation: CancellationToken,
+    capabilities: HashSet<Capability>,
+    operations: u64,
+    depth: usize,
+    // line of the last executed statement, used for errors without a token
+    line: usize,
+    // imports are resolved relative to the script being executed
+    script_path: Option<PathBuf>,
+    // directory of the main script, modules outside of it can't be imported
+    module_root: Option<PathBuf>,
+    modules: HashMap<PathBuf, Rc<Environment>>,
+    // chain of modules being imported at the moment starting from the main script,
+    // to detect cycles
+    importing: Vec<PathBuf>,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
-        Self::default()
+        let builtins = Rc::new(Environment::default());
+        builtins::define_globals(&builtins);
+
+        Self {
+            environment: Rc::new(Environment::with_enclosing(Rc::clone(&builtins))),
+            builtins,
+            capabilities: HashSet::from([Capability::Core]),
+            ..Default::default()
+        }
     }
 
     pub fn attach_reporter<R>(mut self, reporter: Rc<R>) -> Self
@@ -38,43 +230,105 @@ impl Interpreter {
         self
     }
 
+    pub fn attach_debugger<D>(mut self, debugger: D) -> Self
+    where
+        D: DebugHook + 'static,
+    {
+        self.debugger = Some(Box::new(debugger));
+        self
+    }
+
+    pub fn attach_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn attach_capabilities(mut self, capabilities: HashSet<Capability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    pub fn attach_script_path(mut self, script_path: &Path) -> Self {
+        self.script_path = fs::canonicalize(script_path).ok();
+        self.module_root = self
+            .script_path
+            .as_ref()
+            .and_then(|script| script.parent())
+            .map(Path::to_path_buf);
+        self.importing = self.script_path.iter().cloned().collect();
+        self
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
     fn report_runtime_error(&self, error: RuntimeError) {
         if let Some(reporter) = &self.reporter {
             match error {
-                RE::OperandMustBeNumber(operator) => {
-                    reporter.report_runtime(operator.line, "Operand must be a number.")
+                RE::Cancelled(line) => reporter.report_cancelled(line),
+                RE::InModule(_, error) if matches!(*error, RE::Cancelled(_)) => {
+                    reporter.report_cancelled(error.line())
                 }
-                RE::OperandsMustBeNumbers(operator) => {
-                    reporter.report_runtime(operator.line, "Operands must be numbers.")
-                }
-                RE::OperandsMustBeStrings(operator) => {
-                    reporter.report_runtime(operator.line, "Operands must be strings.")
-                }
-                RE::UndefinedVariable(name) => reporter
-                    .report_runtime(name.line, &format!("Undefined variable '{}'.", name.lexeme)),
-                RE::AssignUndefinedVariable(name) => reporter.report_runtime(
-                    name.line,
-                    &format!("Assign undefined variable '{}'.", name.lexeme),
+                RE::InModule(module, error) => reporter.report_runtime(
+                    error.line(),
+                    &format!(" in {}", module),
+                    &error.message(),
                 ),
+                error => reporter.report_runtime(error.line(), "", &error.message()),
             }
         }
     }
 
-    pub fn interpret(&mut self, statements: &[Stmt]) {
+    // returns the code the script asked to exit with, if it called `exit`
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Option<i32> {
+        self.operations = 0;
+
         for statement in statements {
-            if let Err(error) = self.execute(statement) {
-                self.report_runtime_error(error);
-                break;
+            match self.execute(statement) {
+                Ok(_) => {}
+                Err(RE::Exit(_, code)) => return Some(code),
+                Err(error) => {
+                    self.report_runtime_error(error);
+                    break;
+                }
             }
         }
+        None
     }
 
     fn execute(&mut self, stmt: &Stmt) -> Result<Value, RuntimeError> {
+        if let Some(line) = stmt.line() {
+            self.line = line;
+            if let Some(debugger) = self.debugger.as_mut() {
+                debugger.on_statement(
+                    self.script_path.as_deref(),
+                    self.importing.len(),
+                    line,
+                    &self.environment,
+                );
+            }
+        }
+
+        if self.cancellation.is_cancelled() {
+            return Err(RE::Cancelled(self.line));
+        }
+
+        self.enter()?;
+        let result = self.execute_statement(stmt);
+        self.depth -= 1;
+        result
+    }
+
+    fn execute_statement(&mut self, stmt: &Stmt) -> Result<Value, RuntimeError> {
         match stmt {
             Stmt::Expression(expr) => self.evaluate_expr(expr),
-            Stmt::Print(expr) => {
+            Stmt::Print(_, expr) => {
                 let value = self.evaluate_expr(expr)?;
-                println!("{}", value.stringify());
+                match self.debugger.as_mut() {
+                    Some(debugger) => debugger.on_output(&value.stringify()),
+                    None => println!("{}", value.stringify()),
+                }
                 Ok(V::Nil)
             }
             Stmt::Var(name, initializer) => {
@@ -83,24 +337,319 @@ impl Interpreter {
                     value = self.evaluate_expr(expr)?;
                 }
 
+                self.check_redeclaration(name)?;
                 self.environment.define(name.lexeme.to_owned(), value);
                 Ok(V::Nil)
             }
+            Stmt::VarDestructure(target, initializer) => {
+                let value = self.evaluate_expr(initializer)?;
+                let mut bindings = Vec::new();
+                destructure(target, &value, &mut bindings)?;
+
+                for (name, _) in &bindings {
+                    self.check_redeclaration(name)?;
+                }
+                for (name, value) in bindings {
+                    self.environment.define(name.lexeme.to_owned(), value);
+                }
+                Ok(V::Nil)
+            }
+            Stmt::Enum(name, variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|(variant, fields)| {
+                        let fields = fields.iter().map(|field| field.lexeme.to_owned());
+                        (variant.lexeme.to_owned(), fields.collect())
+                    })
+                    .collect();
+                let enum_def = EnumDef {
+                    name: name.lexeme.to_owned(),
+                    variants,
+                };
+
+                self.check_redeclaration(name)?;
+                self.environment
+                    .define_constant(name.lexeme.to_owned(), V::Enum(Rc::new(enum_def)));
+                Ok(V::Nil)
+            }
+            Stmt::Const(name, initializer) => {
+                let value = self.evaluate_expr(initializer)?;
+
+                self.check_redeclaration(name)?;
+                self.environment
+                    .define_constant(name.lexeme.to_owned(), value);
+                Ok(V::Nil)
+            }
             Stmt::Block(statements) => {
-                let previous_env = Rc::clone(&self.environment);
+                self.execute_in_scope(statements, self.block_scope())?;
+                Ok(V::Nil)
+            }
+            Stmt::Throw(keyword, expr) => {
+                let value = self.evaluate_expr(expr)?;
+                Err(RE::Thrown(keyword.clone(), value))
+            }
+            Stmt::ForIn {
+                keyword,
+                name,
+                iterable,
+                body,
+            } => {
+                let iterable = self.evaluate_expr(iterable)?;
+                for item in iteration_items(keyword, &iterable)? {
+                    // fresh scope per iteration, so the loop variable is not shared between them
+                    let scope = self.block_scope();
+                    scope.define(name.lexeme.to_owned(), item);
+                    match self.execute_in_scope(std::slice::from_ref(body.as_ref()), scope) {
+                        Err(RE::Break(_)) => break,
+                        Ok(()) | Err(RE::Continue(_)) => continue,
+                        Err(error) => return Err(error),
+                    }
+                }
+                Ok(V::Nil)
+            }
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let mut result = self.execute_in_scope(body, self.block_scope());
 
-                self.environment = Rc::new(Environment::with_enclosing(Rc::clone(&previous_env)));
+                if let Some((name, handler)) = catch {
+                    result = match result {
+                        Err(error) if error.is_catchable() => {
+                            let scope = self.block_scope();
+                            scope.define(name.lexeme.to_owned(), error_value(error));
+                            self.execute_in_scope(handler, scope)
+                        }
+                        result => result,
+                    };
+                }
+
+                // an error inside of finally replaces the one being propagated
+                if let Some(finally) = finally {
+                    self.execute_in_scope(finally, self.block_scope())?;
+                }
 
-                self.execute_block(statements)?;
+                result.map(|_| V::Nil)
+            }
+            Stmt::Break(keyword) => Err(RE::Break(keyword.clone())),
+            Stmt::Continue(keyword) => Err(RE::Continue(keyword.clone())),
+            Stmt::Import {
+                keyword,
+                path,
+                names,
+            } => {
+                let module = self.load_module(keyword, path)?;
 
-                self.environment = previous_env;
+                let bindings = match names {
+                    Some(names) => {
+                        let mut bindings = Vec::new();
+                        for name in names {
+                            let value = module.get_local(&name.lexeme).ok_or_else(|| {
+                                RE::ImportError(
+                                    name.clone(),
+                                    format!("Module '{}' has no '{}'.", path, name.lexeme),
+                                )
+                            })?;
+                            bindings.push((name.clone(), value));
+                        }
+                        bindings
+                    }
+                    // names of a whole module are reported at the import keyword
+                    None => module
+                        .bindings()
+                        .into_iter()
+                        .map(|(name, value)| {
+                            (Token::new(TT::Identifier, name, keyword.line), value)
+                        })
+                        .collect(),
+                };
 
+                for (name, _) in &bindings {
+                    self.check_redeclaration(name)?;
+                }
+                for (name, value) in bindings {
+                    self.import_binding(&module, name.lexeme, value);
+                }
                 Ok(V::Nil)
             }
         }
     }
 
-    fn execute_block(&mut self, statements: &Vec<Stmt>) -> Result<(), RuntimeError> {
+    // imported constants stay constant in the importing module
+    fn import_binding(&self, module: &Environment, name: String, value: Value) {
+        if module.is_local_constant(&name) {
+            self.environment.define_constant(name, value);
+        } else {
+            self.environment.define(name, value);
+        }
+    }
+
+    // runs a module once in its own top-level scope, later imports reuse the cached scope
+    fn load_module(
+        &mut self,
+        keyword: &Token,
+        path: &str,
+    ) -> Result<Rc<Environment>, RuntimeError> {
+        let base = self
+            .script_path
+            .as_ref()
+            .and_then(|script| script.parent())
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let module_path = fs::canonicalize(base.join(path)).map_err(|_| {
+            RE::ImportError(keyword.clone(), format!("Module '{}' not found.", path))
+        })?;
+
+        // imports would otherwise read any file, bypassing the io.read capability
+        let root = self
+            .module_root
+            .clone()
+            .or_else(|| fs::canonicalize(".").ok())
+            .unwrap_or_default();
+        if !module_path.starts_with(&root) {
+            return Err(RE::ImportError(
+                keyword.clone(),
+                format!("Module '{}' is outside of the script directory.", path),
+            ));
+        }
+
+        if let Some(module) = self.modules.get(&module_path) {
+            return Ok(Rc::clone(module));
+        }
+
+        if let Some(start) = self.importing.iter().position(|p| *p == module_path) {
+            let chain: Vec<String> = self.importing[start..]
+                .iter()
+                .chain([&module_path])
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(RE::ImportError(
+                keyword.clone(),
+                format!("Import cycle detected: {}.", chain.join(" -> ")),
+            ));
+        }
+
+        let source = fs::read_to_string(&module_path).map_err(|error| {
+            RE::ImportError(
+                keyword.clone(),
+                format!("Error reading module '{}': {}.", path, error),
+            )
+        })?;
+
+        let reporter = ModuleReporter::new(self.reporter.clone(), path);
+        let mut scanner = Scanner::new(&source).attach_reporter(Rc::clone(&reporter));
+        let tokens = scanner.scan_tokens();
+        let mut parser = Parser::new(tokens)
+            .attach_reporter(Rc::clone(&reporter))
+            .attach_limits(&self.limits);
+        let statements = parser.parse();
+
+        if reporter.is_had_error() {
+            return Err(RE::ImportError(
+                keyword.clone(),
+                format!("Failed to parse module '{}'.", path),
+            ));
+        }
+
+        let module = Rc::new(Environment::with_enclosing(Rc::clone(&self.builtins)));
+        let previous_env = mem::replace(&mut self.environment, Rc::clone(&module));
+        let previous_path = self.script_path.replace(module_path.clone());
+        self.importing.push(module_path.clone());
+
+        let result = self.execute_block(&statements);
+
+        self.importing.pop();
+        self.script_path = previous_path;
+        self.environment = previous_env;
+
+        match result {
+            Ok(_) => {
+                self.modules.insert(module_path, Rc::clone(&module));
+                Ok(module)
+            }
+            // error is reported by the top level only when nothing catches it
+            Err(error @ (RE::InModule(..) | RE::Exit(..))) => Err(error),
+            Err(error) => Err(RE::InModule(path.to_owned(), Box::new(error))),
+        }
+    }
+
+    fn assign_variable(&self, name: &Token, value: Value) -> Result<(), RuntimeError> {
+        match self.environment.assign(name.lexeme.to_owned(), value) {
+            Ok(_) => Ok(()),
+            Err(EnvError::AssignUndefinedVariable) => {
+                Err(RE::AssignUndefinedVariable(name.clone()))
+            }
+            Err(EnvError::AssignConstant) => Err(RE::AssignConstant(name.clone())),
+        }
+    }
+
+    fn check_redeclaration(&self, name: &Token) -> Result<(), RuntimeError> {
+        if self.environment.is_local_constant(&name.lexeme) {
+            Err(RE::RedeclareConstant(name.clone()))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn evaluate_match(
+        &mut self,
+        keyword: &Token,
+        value: &Expr,
+        arms: &[MatchArm],
+    ) -> Result<Value, RuntimeError> {
+        let value = self.evaluate_expr(value)?;
+
+        for arm in arms {
+            let mut bindings = Vec::new();
+            if !match_pattern(&arm.pattern, &value, &self.environment, &mut bindings)? {
+                continue;
+            }
+
+            let scope = self.block_scope();
+            for (name, bound) in bindings {
+                scope.define(name, bound);
+            }
+            let previous_env = mem::replace(&mut self.environment, scope);
+            let result = self.evaluate_arm(arm);
+            self.environment = previous_env;
+
+            if let Some(result) = result? {
+                return Ok(result);
+            }
+        }
+
+        Err(RE::NoMatchingArm(keyword.clone(), value))
+    }
+
+    // none when the guard rejects the arm
+    fn evaluate_arm(&mut self, arm: &MatchArm) -> Result<Option<Value>, RuntimeError> {
+        if let Some(guard) = &arm.guard {
+            if !is_truthy(&self.evaluate_expr(guard)?) {
+                return Ok(None);
+            }
+        }
+        self.evaluate_expr(&arm.body).map(Some)
+    }
+
+    fn block_scope(&self) -> Rc<Environment> {
+        Rc::new(Environment::with_enclosing(Rc::clone(&self.environment)))
+    }
+
+    // previous environment is restored even when statements fail, so execution can
+    // continue in the right scope after the error is caught
+    fn execute_in_scope(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<Environment>,
+    ) -> Result<(), RuntimeError> {
+        let previous_env = mem::replace(&mut self.environment, environment);
+        let result = self.execute_block(statements);
+        self.environment = previous_env;
+        result
+    }
+
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
         for statement in statements {
             self.execute(statement)?;
         }
@@ -109,8 +658,65 @@ impl Interpreter {
     }
 
     fn evaluate_expr(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.enter()?;
+        let result = self.evaluate(expr);
+        self.depth -= 1;
+        result
+    }
+
+    // counts an operation and a nesting level against the limits,
+    // caller is responsible for decrementing depth when leaving
+    fn enter(&mut self) -> Result<(), RuntimeError> {
+        self.operations += 1;
+        if self
+            .limits
+            .max_operations
+            .is_some_and(|max| self.operations > max)
+        {
+            return Err(RE::OperationLimitExceeded(self.line));
+        }
+        if self.limits.max_depth.is_some_and(|max| self.depth >= max) {
+            return Err(RE::DepthLimitExceeded(self.line));
+        }
+        if self
+            .limits
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(RE::DeadlineExceeded(self.line));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
         match expr {
             Expr::Literal(val) => Ok(val.clone()),
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if is_truthy(&self.evaluate_expr(condition)?) {
+                    self.evaluate_expr(then_branch)
+                } else {
+                    self.evaluate_expr(else_branch)
+                }
+            }
+            Expr::Coalesce { left, right, .. } => {
+                let left_value = self.evaluate_expr(left)?;
+                if left_value.is_nil() {
+                    self.evaluate_expr(right)
+                } else {
+                    Ok(left_value)
+                }
+            }
+            Expr::Match {
+                keyword,
+                value,
+                arms,
+            } => self.evaluate_match(keyword, value, arms),
             Expr::Grouping(expr) => self.evaluate_expr(expr),
             Expr::Unary { operator, right } => self.evaluate_unary(operator, right),
             Expr::Binary {
@@ -121,26 +727,201 @@ impl Interpreter {
             Expr::Variable(name) => self.evaluate_variable(name),
             Expr::Assign(name, expr) => {
                 let value = self.evaluate_expr(expr)?;
-                match self
-                    .environment
-                    .assign(name.lexeme.to_owned(), value.clone())
-                {
-                    Ok(_) => Ok(value),
-                    Err(_) => Err(RE::AssignUndefinedVariable(name.clone())),
+                self.assign_variable(name, value.clone())?;
+                Ok(value)
+            }
+            Expr::AssignDestructure(target, expr) => {
+                let value = self.evaluate_expr(expr)?;
+                let mut bindings = Vec::new();
+                destructure(target, &value, &mut bindings)?;
+
+                for (name, bound) in bindings {
+                    self.assign_variable(name, bound)?;
+                }
+                Ok(value)
+            }
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } => self.evaluate_call(callee, paren, arguments),
+            Expr::Get { object, name } => self.evaluate_get(object, name),
+            Expr::Postfix { name, operator } => self.evaluate_postfix(name, operator),
+            Expr::List(elements) => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.evaluate_expr(element)?);
+                }
+                Ok(V::List(Rc::new(RefCell::new(values))))
+            }
+            Expr::Interpolation(parts) => {
+                let mut result = String::new();
+                for part in parts {
+                    result.push_str(&self.evaluate_expr(part)?.stringify());
+                }
+                Ok(V::String(result))
+            }
+            Expr::Map { brace, entries } => {
+                let mut map = BTreeMap::new();
+                for (key, value) in entries {
+                    let key = self.evaluate_expr(key)?;
+                    let key = map_key(brace, &key)?;
+                    map.insert(key, self.evaluate_expr(value)?);
+                }
+                Ok(V::Map(Rc::new(RefCell::new(map))))
+            }
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let object = self.evaluate_expr(object)?;
+                let index_value = self.evaluate_expr(index)?;
+
+                match &object {
+                    V::List(list) => {
+                        let list = list.borrow();
+                        let index = element_index(bracket, &index_value, list.len())?;
+                        Ok(list[index].clone())
+                    }
+                    // missing key is nil, use `has` to tell it apart from a stored nil
+                    V::Map(map) => {
+                        let key = map_key(bracket, &index_value)?;
+                        Ok(map.borrow().get(&key).cloned().unwrap_or(V::Nil))
+                    }
+                    _ => Err(RE::NotIndexable(bracket.clone())),
+                }
+            }
+            Expr::SetIndex {
+                object,
+                bracket,
+                index,
+                value,
+            } => {
+                let object = self.evaluate_expr(object)?;
+                let index_value = self.evaluate_expr(index)?;
+                let value = self.evaluate_expr(value)?;
+
+                match &object {
+                    V::List(list) => {
+                        let mut list = list.borrow_mut();
+                        let index = element_index(bracket, &index_value, list.len())?;
+                        list[index] = value.clone();
+                    }
+                    V::Map(map) => {
+                        let key = map_key(bracket, &index_value)?;
+                        map.borrow_mut().insert(key, value.clone());
+                    }
+                    _ => return Err(RE::NotIndexable(bracket.clone())),
                 }
+                Ok(value)
             }
         }
     }
 
+    fn evaluate_call(
+        &mut self,
+        callee: &Expr,
+        paren: &Token,
+        arguments: &[Expr],
+    ) -> Result<Value, RuntimeError> {
+        let callee_value = self.evaluate_expr(callee)?;
+
+        let mut argument_values = Vec::new();
+        for argument in arguments {
+            argument_values.push(self.evaluate_expr(argument)?);
+        }
+
+        match &callee_value {
+            V::Native(native) => self.call_native(native, paren, &argument_values),
+            &V::VariantConstructor(ref enum_def, index) => {
+                let fields = enum_def.variants[index].1.len();
+                if argument_values.len() != fields {
+                    return Err(RE::ArityMismatch(
+                        paren.clone(),
+                        fields,
+                        argument_values.len(),
+                    ));
+                }
+                Ok(V::Variant(Rc::new(EnumVariant {
+                    enum_def: Rc::clone(enum_def),
+                    index,
+                    payload: argument_values,
+                })))
+            }
+            _ => Err(RE::NotCallable(paren.clone())),
+        }
+    }
+
+    fn evaluate_get(&mut self, object: &Expr, name: &Token) -> Result<Value, RuntimeError> {
+        match &self.evaluate_expr(object)? {
+            V::Enum(enum_def) => {
+                let index = enum_def
+                    .variant_index(&name.lexeme)
+                    .ok_or_else(|| RE::UndefinedVariant(name.clone(), enum_def.name.clone()))?;
+
+                // variants with payload have to be called to get a value
+                if enum_def.variants[index].1.is_empty() {
+                    Ok(V::Variant(Rc::new(EnumVariant {
+                        enum_def: Rc::clone(enum_def),
+                        index,
+                        payload: Vec::new(),
+                    })))
+                } else {
+                    Ok(V::VariantConstructor(Rc::clone(enum_def), index))
+                }
+            }
+            V::Variant(variant) => variant
+                .field(&name.lexeme)
+                .cloned()
+                .ok_or_else(|| RE::UndefinedProperty(name.clone())),
+            V::VariantConstructor(enum_def, index) => Err(RE::VariantNotCalled(
+                name.clone(),
+                enum_def.variants[*index].0.to_owned(),
+            )),
+            _ => Err(RE::NoProperties(name.clone())),
+        }
+    }
+
+    fn call_native(
+        &self,
+        native: &NativeFunction,
+        paren: &Token,
+        arguments: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        if !self.capabilities.contains(&native.capability) {
+            return Err(RE::CapabilityDenied(
+                paren.clone(),
+                native.name,
+                native.capability,
+            ));
+        }
+        if arguments.len() != native.arity {
+            return Err(RE::ArityMismatch(
+                paren.clone(),
+                native.arity,
+                arguments.len(),
+            ));
+        }
+
+        (native.function)(arguments).map_err(|error| match error {
+            NativeError::Message(message) => RE::NativeError(paren.clone(), message),
+            NativeError::Exit(code) => RE::Exit(paren.clone(), code),
+        })
+    }
+
     fn evaluate_unary(&mut self, operator: &Token, right: &Expr) -> Result<Value, RuntimeError> {
         let right_value = self.evaluate_expr(right)?;
 
         match operator.token_type {
             TT::Bang => Ok(V::Bool(!is_truthy(&right_value))),
-            TT::Minus => {
-                let right_num = expect_number_operand(operator, right_value)?;
-                Ok(V::Number(-right_num))
-            }
+            TT::Minus => match right_value {
+                V::Integer(int) => integer_result(operator, int.checked_neg()),
+                _ => {
+                    let right_num = expect_number_operand(operator, right_value)?;
+                    Ok(V::Number(-right_num))
+                }
+            },
             _ => Ok(V::Nil),
         }
     }
@@ -160,50 +941,107 @@ impl Interpreter {
     ) -> Result<Value, RuntimeError> {
         let left_value = self.evaluate_expr(left)?;
         let right_value = self.evaluate_expr(right)?;
+        self.binary_operation(operator, left_value, right_value)
+    }
 
+    // `a++` stores the incremented value, but evaluates to the one read before
+    fn evaluate_postfix(&mut self, name: &Token, operator: &Token) -> Result<Value, RuntimeError> {
+        let step = if operator.token_type.variant_eq(&TT::PlusPlus) {
+            TT::Plus
+        } else {
+            TT::Minus
+        };
+        let arithmetic = Token::new(step, operator.lexeme[..1].to_owned(), operator.line);
+
+        let previous = self.evaluate_variable(name)?;
+        let updated = self.binary_operation(&arithmetic, previous.clone(), V::Integer(1))?;
+        self.assign_variable(name, updated)?;
+        Ok(previous)
+    }
+
+    fn binary_operation(
+        &self,
+        operator: &Token,
+        left_value: Value,
+        right_value: Value,
+    ) -> Result<Value, RuntimeError> {
         match operator.token_type {
             TT::BangEqual => Ok(V::Bool(!is_equal(&left_value, &right_value))),
-            TT::Equal => Ok(V::Bool(is_equal(&left_value, &right_value))),
-            TT::Greater => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Bool(left_num > right_num))
-            }
-            TT::GreaterEqual => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Bool(left_num >= right_num))
-            }
-            TT::Less => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Bool(left_num < right_num))
-            }
-            TT::LessEqual => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Bool(left_num <= right_num))
-            }
-            TT::Minus => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Number(left_num - right_num))
-            }
+            TT::EqualEqual => Ok(V::Bool(is_equal(&left_value, &right_value))),
+            TT::Greater => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => Ok(V::Bool(left > right)),
+                Operands::Floats(left, right) => Ok(V::Bool(left > right)),
+            },
+            TT::GreaterEqual => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => Ok(V::Bool(left >= right)),
+                Operands::Floats(left, right) => Ok(V::Bool(left >= right)),
+            },
+            TT::Less => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => Ok(V::Bool(left < right)),
+                Operands::Floats(left, right) => Ok(V::Bool(left < right)),
+            },
+            TT::LessEqual => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => Ok(V::Bool(left <= right)),
+                Operands::Floats(left, right) => Ok(V::Bool(left <= right)),
+            },
+            TT::Minus => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => {
+                    integer_result(operator, left.checked_sub(right))
+                }
+                Operands::Floats(left, right) => Ok(V::Number(left - right)),
+            },
+            // always true division, integer division is the `div` builtin
             TT::Slash => {
                 let (left_num, right_num) =
                     expect_number_operands(operator, left_value, right_value)?;
                 Ok(V::Number(left_num / right_num))
             }
-            TT::Star => {
-                let (left_num, right_num) =
-                    expect_number_operands(operator, left_value, right_value)?;
-                Ok(V::Number(left_num * right_num))
-            }
+            TT::Star => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(left, right) => {
+                    integer_result(operator, left.checked_mul(right))
+                }
+                Operands::Floats(left, right) => Ok(V::Number(left * right)),
+            },
+            // remainder of the division rounded down, like `div`, so it takes the sign
+            // of the divisor and `div(a, b) * b + a % b == a`
+            TT::Percent => match number_operands(operator, left_value, right_value)? {
+                Operands::Integers(_, 0) => Err(RE::DivisionByZero(operator.clone())),
+                Operands::Integers(left, right) => {
+                    let rem = left.wrapping_rem(right);
+                    if rem != 0 && (rem < 0) != (right < 0) {
+                        Ok(V::Integer(rem + right))
+                    } else {
+                        Ok(V::Integer(rem))
+                    }
+                }
+                Operands::Floats(left, right) => {
+                    let rem = left % right;
+                    if rem != 0.0 && (rem < 0.0) != (right < 0.0) {
+                        Ok(V::Number(rem + right))
+                    } else {
+                        Ok(V::Number(rem))
+                    }
+                }
+            },
+            TT::StarStar => match number_operands(operator, left_value, right_value)? {
+                // negative exponents give fractions, so they fall back to floats
+                Operands::Integers(left, right) if right >= 0 => {
+                    let power = u32::try_from(right)
+                        .ok()
+                        .and_then(|right| left.checked_pow(right));
+                    integer_result(operator, power)
+                }
+                Operands::Integers(left, right) => Ok(V::Number((left as f64).powf(right as f64))),
+                Operands::Floats(left, right) => Ok(V::Number(left.powf(right))),
+            },
             TT::Plus => {
                 if left_value.is_number() && right_value.is_number() {
-                    let (left_num, right_num) =
-                        expect_number_operands(operator, left_value, right_value)?;
-                    Ok(V::Number(left_num + right_num))
+                    match number_operands(operator, left_value, right_value)? {
+                        Operands::Integers(left, right) => {
+                            integer_result(operator, left.checked_add(right))
+                        }
+                        Operands::Floats(left, right) => Ok(V::Number(left + right)),
+                    }
                 } else {
                     let (left_str, right_str) =
                         expect_string_operands(operator, left_value, right_value)?;
@@ -215,20 +1053,328 @@ impl Interpreter {
     }
 }
 
-fn is_equal(left: &Value, right: &Value) -> bool {
+// nested collections are compared from a worklist rather than recursively, so deep
+// nesting can't overflow the stack
+pub fn is_equal(left: &Value, right: &Value) -> bool {
+    let mut pending = Vec::new();
+    let mut compared = HashSet::new();
+    if !values_equal(left, right, &mut pending, &mut compared) {
+        return false;
+    }
+    while let Some((left, right)) = pending.pop() {
+        if !values_equal(&left, &right, &mut pending, &mut compared) {
+            return false;
+        }
+    }
+    true
+}
+
+type Pending = Vec<(Value, Value)>;
+
+// elements of collections are pushed to pending instead of being compared here.
+// pairs of lists or maps already compared are assumed equal, so self-containing
+// collections don't loop forever
+fn values_equal(
+    left: &Value,
+    right: &Value,
+    pending: &mut Pending,
+    compared: &mut HashSet<(*const (), *const ())>,
+) -> bool {
     if left.is_nil() && right.is_nil() {
         true
+    } else if let (Value::Integer(left), Value::Integer(right)) = (left, right) {
+        left == right
     } else if left.is_number() && right.is_number() {
         left.as_number().unwrap() == right.as_number().unwrap()
     } else if left.is_string() && right.is_string() {
         left.as_string().unwrap() == right.as_string().unwrap()
     } else if left.is_bool() && right.is_bool() {
         left.as_bool().unwrap() == right.as_bool().unwrap()
+    } else if let (Value::Native(left), Value::Native(right)) = (left, right) {
+        Rc::ptr_eq(left, right)
+    } else if let (Value::List(left), Value::List(right)) = (left, right) {
+        let pair = (
+            Rc::as_ptr(left).cast::<()>(),
+            Rc::as_ptr(right).cast::<()>(),
+        );
+        if Rc::ptr_eq(left, right) || !compared.insert(pair) {
+            return true;
+        }
+
+        let (left, right) = (left.borrow(), right.borrow());
+        left.len() == right.len()
+            && left
+                .iter()
+                .zip(right.iter())
+                .all(|(l, r)| elements_equal(l, r, pending, compared))
+    } else if let (Value::Range(left), Value::Range(right)) = (left, right) {
+        left == right
+    } else if let (Value::Enum(left), Value::Enum(right)) = (left, right) {
+        Rc::ptr_eq(left, right)
+    } else if let (Value::Variant(left), Value::Variant(right)) = (left, right) {
+        Rc::ptr_eq(&left.enum_def, &right.enum_def)
+            && left.index == right.index
+            && left
+                .payload
+                .iter()
+                .zip(right.payload.iter())
+                .all(|(l, r)| elements_equal(l, r, pending, compared))
+    } else if let (
+        Value::VariantConstructor(left, left_index),
+        Value::VariantConstructor(right, right_index),
+    ) = (left, right)
+    {
+        Rc::ptr_eq(left, right) && left_index == right_index
+    } else if let (Value::Map(left), Value::Map(right)) = (left, right) {
+        let pair = (
+            Rc::as_ptr(left).cast::<()>(),
+            Rc::as_ptr(right).cast::<()>(),
+        );
+        if Rc::ptr_eq(left, right) || !compared.insert(pair) {
+            return true;
+        }
+
+        let (left, right) = (left.borrow(), right.borrow());
+        left.len() == right.len()
+            && left
+                .iter()
+                .zip(right.iter())
+                .all(|((lk, lv), (rk, rv))| lk == rk && elements_equal(lv, rv, pending, compared))
     } else {
         false
     }
 }
 
+// two collections are left for later, anything else is compared right away
+fn elements_equal(
+    left: &Value,
+    right: &Value,
+    pending: &mut Pending,
+    compared: &mut HashSet<(*const (), *const ())>,
+) -> bool {
+    let nested = |value: &Value| matches!(value, V::List(_) | V::Map(_) | V::Variant(_));
+    if nested(left) && nested(right) {
+        pending.push((left.clone(), right.clone()));
+        true
+    } else {
+        values_equal(left, right, pending, compared)
+    }
+}
+
+fn element_index(bracket: &Token, index: &Value, len: usize) -> Result<usize, RuntimeError> {
+    if len == 0 {
+        return Err(RE::IndexOutOfBounds(
+            bracket.clone(),
+            "Can't index an empty list.".to_owned(),
+        ));
+    }
+    lists::to_index(index, len - 1)
+        .map_err(|message| RE::IndexOutOfBounds(bracket.clone(), message))
+}
+
+// takes the value apart into the names of the target, failing when its shape is different
+fn destructure<'a>(
+    target: &'a Destructure,
+    value: &Value,
+    bindings: &mut Vec<(&'a Token, Value)>,
+) -> Result<(), RuntimeError> {
+    match target {
+        Destructure::Name(name) => bindings.push((name, value.clone())),
+        Destructure::List(bracket, targets) => {
+            let V::List(list) = value else {
+                return Err(RE::DestructureMismatch(
+                    bracket.clone(),
+                    "Only lists can be destructured with '[...]'.".to_owned(),
+                ));
+            };
+            let list = list.borrow();
+            if list.len() != targets.len() {
+                return Err(RE::DestructureMismatch(
+                    bracket.clone(),
+                    format!(
+                        "Expected a list of {} elements but got {}.",
+                        targets.len(),
+                        list.len()
+                    ),
+                ));
+            }
+            for (target, element) in targets.iter().zip(list.iter()) {
+                destructure(target, element, bindings)?;
+            }
+        }
+        Destructure::Map(brace, names) => {
+            let V::Map(map) = value else {
+                return Err(RE::DestructureMismatch(
+                    brace.clone(),
+                    "Only maps can be destructured with '{...}'.".to_owned(),
+                ));
+            };
+            let map = map.borrow();
+            for name in names {
+                let entry = map.get(&MapKey::String(name.lexeme.to_owned()));
+                let Some(entry) = entry else {
+                    return Err(RE::DestructureMismatch(
+                        name.clone(),
+                        format!("Map has no key '{}'.", name.lexeme),
+                    ));
+                };
+                bindings.push((name, entry.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+// collects bound names on success, bindings of failed alternatives are dropped
+fn match_pattern(
+    pattern: &Pattern,
+    value: &Value,
+    environment: &Environment,
+    bindings: &mut Vec<(String, Value)>,
+) -> Result<bool, RuntimeError> {
+    match pattern {
+        Pattern::Wildcard => Ok(true),
+        Pattern::Literal(literal) => Ok(is_equal(literal, value)),
+        Pattern::Binding(name) => {
+            bindings.push((name.lexeme.to_owned(), value.clone()));
+            Ok(true)
+        }
+        Pattern::List(patterns) => match value {
+            V::List(list) => {
+                let list = list.borrow();
+                Ok(list.len() == patterns.len()
+                    && match_elements(patterns, &list, environment, bindings)?)
+            }
+            _ => Ok(false),
+        },
+        Pattern::Variant {
+            enum_name,
+            variant,
+            payload,
+        } => {
+            let (enum_def, index) = pattern_variant(environment, enum_name, variant)?;
+            match value {
+                // enums are told apart by identity, two enums may share a name
+                V::Variant(value)
+                    if Rc::ptr_eq(&value.enum_def, &enum_def) && value.index == index =>
+                {
+                    match payload {
+                        Some(patterns) => {
+                            let fields = enum_def.variants[index].1.len();
+                            if patterns.len() != fields {
+                                return Err(RE::PayloadMismatch(
+                                    variant.clone(),
+                                    fields,
+                                    patterns.len(),
+                                ));
+                            }
+                            match_elements(patterns, &value.payload, environment, bindings)
+                        }
+                        None => Ok(true),
+                    }
+                }
+                _ => Ok(false),
+            }
+        }
+        Pattern::Alternatives(alternatives) => {
+            for alternative in alternatives {
+                let bound = bindings.len();
+                if match_pattern(alternative, value, environment, bindings)? {
+                    return Ok(true);
+                }
+                bindings.truncate(bound);
+            }
+            Ok(false)
+        }
+    }
+}
+
+fn match_elements(
+    patterns: &[Pattern],
+    values: &[Value],
+    environment: &Environment,
+    bindings: &mut Vec<(String, Value)>,
+) -> Result<bool, RuntimeError> {
+    for (pattern, value) in patterns.iter().zip(values) {
+        if !match_pattern(pattern, value, environment, bindings)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// enum and variant index a variant pattern refers to, resolved like `Enum.Variant`
+fn pattern_variant(
+    environment: &Environment,
+    enum_name: &Token,
+    variant: &Token,
+) -> Result<(Rc<EnumDef>, usize), RuntimeError> {
+    match &environment.get(enum_name) {
+        Some(V::Enum(enum_def)) => {
+            let index = enum_def
+                .variant_index(&variant.lexeme)
+                .ok_or_else(|| RE::UndefinedVariant(variant.clone(), enum_def.name.clone()))?;
+            Ok((Rc::clone(enum_def), index))
+        }
+        Some(_) => Err(RE::NoProperties(variant.clone())),
+        None => Err(RE::UndefinedVariable(enum_name.clone())),
+    }
+}
+
+// items of collections are collected up front, so changing a collection while
+// walking it does not affect the loop. ranges are stepped lazily instead
+fn iteration_items(
+    keyword: &Token,
+    iterable: &Value,
+) -> Result<Box<dyn Iterator<Item = Value>>, RuntimeError> {
+    match iterable {
+        V::List(list) => Ok(Box::new(list.borrow().clone().into_iter())),
+        V::Map(map) => {
+            let keys: Vec<Value> = map.borrow().keys().map(MapKey::to_value).collect();
+            Ok(Box::new(keys.into_iter()))
+        }
+        V::String(str) => {
+            let chars: Vec<Value> = str
+                .chars()
+                .map(|char| V::String(char.to_string()))
+                .collect();
+            Ok(Box::new(chars.into_iter()))
+        }
+        V::Range(range) => {
+            let range = *range;
+            Ok(Box::new(
+                (0..range.count()).map(move |index| range.get(index)),
+            ))
+        }
+        _ => Err(RE::NotIterable(keyword.clone())),
+    }
+}
+
+// thrown values are caught as is, other errors as a map of their message and line
+fn error_value(error: RuntimeError) -> Value {
+    match error {
+        RE::Thrown(_, value) => value,
+        RE::InModule(_, error) => error_value(*error),
+        error => {
+            let map = BTreeMap::from([
+                (
+                    MapKey::String("message".to_owned()),
+                    V::String(error.message()),
+                ),
+                (
+                    MapKey::String("line".to_owned()),
+                    V::Integer(error.line() as i64),
+                ),
+            ]);
+            V::Map(Rc::new(RefCell::new(map)))
+        }
+    }
+}
+
+fn map_key(token: &Token, key: &Value) -> Result<MapKey, RuntimeError> {
+    MapKey::from_value(key).ok_or_else(|| RE::InvalidMapKey(token.clone()))
+}
+
 fn is_truthy(value: &Value) -> bool {
     match value {
         Value::Nil => false,
@@ -242,6 +1388,28 @@ fn expect_number_operand(operator: &Token, val: Value) -> Result<f64, RuntimeErr
         .ok_or_else(|| RE::OperandMustBeNumber(operator.clone()))
 }
 
+// integers stay integers, mixing in a float makes both floats
+enum Operands {
+    Integers(i64, i64),
+    Floats(f64, f64),
+}
+
+fn number_operands(operator: &Token, left: Value, right: Value) -> Result<Operands, RuntimeError> {
+    match (left.as_integer(), right.as_integer()) {
+        (Some(left), Some(right)) => Ok(Operands::Integers(left, right)),
+        _ => {
+            let (left, right) = expect_number_operands(operator, left, right)?;
+            Ok(Operands::Floats(left, right))
+        }
+    }
+}
+
+fn integer_result(operator: &Token, result: Option<i64>) -> Result<Value, RuntimeError> {
+    result
+        .map(V::Integer)
+        .ok_or_else(|| RE::IntegerOverflow(operator.clone()))
+}
+
 fn expect_number_operands(
     operator: &Token,
     left: Value,
@@ -0,0 +1,14 @@
+use std::time::Instant;
+
+// bounds for running untrusted scripts, every limit is disabled by default
+#[derive(Debug, Default, Clone)]
+pub struct Limits {
+    // maximum amount of statements executed and expressions evaluated
+    pub max_operations: Option<u64>,
+    // maximum nesting of statements and expressions being evaluated at once,
+    // interpreter recurses natively so this is what keeps rust stack from overflowing
+    pub max_depth: Option<usize>,
+    // maximum nesting of blocks and expressions accepted by the parser
+    pub max_nesting: Option<usize>,
+    pub deadline: Option<Instant>,
+}
@@ -1,6 +1,10 @@
 pub mod ast;
+pub mod builtins;
+pub mod cancellation;
+pub mod debugger;
 pub mod environment;
 pub mod interpreter;
+pub mod limits;
 pub mod parser;
 pub mod reporter;
 pub mod scanner;
@@ -1,7 +1,8 @@
-use std::rc::Rc;
+use std::{collections::HashMap, rc::Rc};
 
 use super::{
-    ast::{Expr, Stmt, Value},
+    ast::{Destructure, Expr, MatchArm, Pattern, Stmt, Value},
+    limits::Limits,
     reporter::ErrorReporter,
     tokens::{Token, TokenType},
 };
@@ -13,11 +14,26 @@ use Value as V;
 pub enum ParseError {
     ExpectExpression,
     ConsumeUntilTokenNotFound,
+    TooDeeplyNested,
+}
+
+// what is known about a name at parse time
+struct Declaration {
+    constant: bool,
+    // variant names with their field count when the name is an enum
+    variants: Option<Vec<(String, usize)>>,
 }
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    depth: usize,
+    // amount of loops enclosing the statement being parsed, `break` and `continue` need one
+    loop_depth: usize,
+    // names declared in each block being parsed, mapped to whether they are constant,
+    // so assignments to constants visible here are rejected before running the script
+    scopes: Vec<HashMap<String, Declaration>>,
+    max_nesting: Option<usize>,
     reporter: Option<Rc<dyn ErrorReporter>>,
 }
 
@@ -28,10 +44,19 @@ impl Parser {
         Self {
             tokens,
             current: 0,
+            depth: 0,
+            loop_depth: 0,
+            scopes: vec![HashMap::new()],
+            max_nesting: None,
             reporter: None,
         }
     }
 
+    pub fn attach_limits(mut self, limits: &Limits) -> Self {
+        self.max_nesting = limits.max_nesting;
+        self
+    }
+
     pub fn attach_reporter<R>(mut self, reporter: Rc<R>) -> Self
     where
         R: ErrorReporter + 'static,
@@ -50,6 +75,12 @@ impl Parser {
         }
     }
 
+    fn report_warning(&self, token: &Token, message: &str) {
+        if let Some(reporter) = self.reporter.as_ref() {
+            reporter.report_warning(token.line, &format!(" at '{}'", token.lexeme), message);
+        }
+    }
+
     pub fn parse(&mut self) -> Vec<Stmt> {
         let mut statements = Vec::new();
 
@@ -68,12 +99,22 @@ impl Parser {
     fn declaration(&mut self) -> Result<Stmt> {
         if self.consume_matches(&[TT::Var]) {
             self.var_declaration()
+        } else if self.consume_matches(&[TT::Const]) {
+            self.const_declaration()
+        } else if self.consume_matches(&[TT::Import]) {
+            self.import_declaration()
+        } else if self.consume_matches(&[TT::Enum]) {
+            self.enum_declaration()
         } else {
             self.statement()
         }
     }
 
     fn var_declaration(&mut self) -> Result<Stmt> {
+        if self.check(&TT::LeftBracket) || self.check(&TT::LeftBrace) {
+            return self.var_destructure_declaration();
+        }
+
         let name = self.consume_expected(TT::Identifier, "Expect variable name.")?;
 
         let mut initializer = None;
@@ -83,23 +124,288 @@ impl Parser {
         }
 
         self.consume_expected(TT::Semicolon, "Expect ';' after variable declaration.")?;
+        self.declare(&name, false);
         Ok(Stmt::Var(name, initializer))
     }
 
+    fn enum_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume_expected(TT::Identifier, "Expect enum name.")?;
+        self.consume_expected(TT::LeftBrace, "Expect '{' before enum variants.")?;
+
+        let mut variants: Vec<(Token, Vec<Token>)> = Vec::new();
+        while !self.check(&TT::RightBrace) && !self.is_at_end() {
+            let variant = self.consume_expected(TT::Identifier, "Expect variant name.")?;
+            if variants
+                .iter()
+                .any(|(existing, _)| existing.lexeme == variant.lexeme)
+            {
+                self.report_error(
+                    &variant,
+                    &format!("Already a variant named '{}' in this enum.", variant.lexeme),
+                );
+            }
+
+            let mut fields = Vec::new();
+            if self.consume_matches(&[TT::LeftParen]) {
+                loop {
+                    let field = self.consume_expected(TT::Identifier, "Expect field name.")?;
+                    if fields
+                        .iter()
+                        .any(|existing: &Token| existing.lexeme == field.lexeme)
+                    {
+                        self.report_error(
+                            &field,
+                            &format!("Already a field named '{}' in this variant.", field.lexeme),
+                        );
+                    }
+                    fields.push(field);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+                self.consume_expected(TT::RightParen, "Expect ')' after variant fields.")?;
+            }
+            variants.push((variant, fields));
+
+            if !self.consume_matches(&[TT::Comma]) {
+                break;
+            }
+        }
+        self.consume_expected(TT::RightBrace, "Expect '}' after enum variants.")?;
+
+        self.declare(&name, true);
+        if let Some(declaration) = self
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.get_mut(&name.lexeme))
+        {
+            declaration.variants = Some(
+                variants
+                    .iter()
+                    .map(|(variant, fields)| (variant.lexeme.to_owned(), fields.len()))
+                    .collect(),
+            );
+        }
+        Ok(Stmt::Enum(name, variants))
+    }
+
+    fn var_destructure_declaration(&mut self) -> Result<Stmt> {
+        let target = self.destructure()?;
+        self.consume_expected(TT::Equal, "Expect '=' after destructuring pattern.")?;
+        let initializer = self.expression()?;
+
+        self.consume_expected(TT::Semicolon, "Expect ';' after variable declaration.")?;
+        for name in target.names() {
+            self.declare(name, false);
+        }
+        Ok(Stmt::VarDestructure(target, initializer))
+    }
+
+    fn destructure(&mut self) -> Result<Destructure> {
+        if self.consume_matches(&[TT::LeftBracket]) {
+            let bracket = self.previous();
+            let mut elements = Vec::new();
+            if !self.check(&TT::RightBracket) {
+                loop {
+                    elements.push(self.nested(Self::destructure)?);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume_expected(TT::RightBracket, "Expect ']' after destructured names.")?;
+            Ok(Destructure::List(bracket, elements))
+        } else if self.consume_matches(&[TT::LeftBrace]) {
+            let brace = self.previous();
+            let mut names = Vec::new();
+            if !self.check(&TT::RightBrace) {
+                loop {
+                    names.push(self.consume_expected(TT::Identifier, "Expect variable name.")?);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume_expected(TT::RightBrace, "Expect '}' after destructured names.")?;
+            Ok(Destructure::Map(brace, names))
+        } else {
+            let name = self.consume_expected(TT::Identifier, "Expect variable name.")?;
+            Ok(Destructure::Name(name))
+        }
+    }
+
+    fn const_declaration(&mut self) -> Result<Stmt> {
+        let name = self.consume_expected(TT::Identifier, "Expect constant name.")?;
+        self.consume_expected(TT::Equal, "Expect '=' after constant name.")?;
+        let initializer = self.expression()?;
+
+        self.consume_expected(TT::Semicolon, "Expect ';' after constant declaration.")?;
+        self.declare(&name, true);
+        Ok(Stmt::Const(name, initializer))
+    }
+
+    fn import_declaration(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let mut names = None;
+
+        if self.consume_matches(&[TT::LeftBrace]) {
+            let mut imported = Vec::new();
+            loop {
+                imported.push(self.consume_expected(TT::Identifier, "Expect name to import.")?);
+                if !self.consume_matches(&[TT::Comma]) {
+                    break;
+                }
+            }
+            self.consume_expected(TT::RightBrace, "Expect '}' after imported names.")?;
+
+            // `from` is not reserved, so it stays usable as a variable name
+            if !(self.check(&TT::Identifier) && self.peek().lexeme == "from") {
+                self.report_error(self.peek(), "Expect 'from' after imported names.");
+                return Err(ParseError::ConsumeUntilTokenNotFound);
+            }
+            self.consume();
+            for name in &imported {
+                self.declare(name, false);
+            }
+            names = Some(imported);
+        }
+
+        let path_token =
+            self.consume_expected(TT::String("".into()), "Expect module path string.")?;
+        let path = match path_token.token_type {
+            TT::String(path) => path,
+            _ => panic!("the module path is not a string despite enum match"),
+        };
+
+        self.consume_expected(TT::Semicolon, "Expect ';' after import.")?;
+        Ok(Stmt::Import {
+            keyword,
+            path,
+            names,
+        })
+    }
+
     fn statement(&mut self) -> Result<Stmt> {
         if self.consume_matches(&[TT::Print]) {
             self.print_statement()
         } else if self.consume_matches(&[TT::LeftBrace]) {
-            Ok(Stmt::Block(Box::new(self.block()?)))
+            Ok(Stmt::Block(Box::new(self.nested(Self::block)?)))
+        } else if self.consume_matches(&[TT::For]) {
+            self.for_in_statement()
+        } else if self.consume_matches(&[TT::Break, TT::Continue]) {
+            self.loop_jump_statement()
+        } else if self.consume_matches(&[TT::Throw]) {
+            self.throw_statement()
+        } else if self.consume_matches(&[TT::Try]) {
+            self.try_statement()
         } else {
             self.expression_statement()
         }
     }
 
+    fn for_in_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        self.consume_expected(TT::LeftParen, "Expect '(' after 'for'.")?;
+        let name = self.consume_expected(TT::Identifier, "Expect loop variable name.")?;
+
+        // `in` is not reserved, so it stays usable as a variable name
+        if !(self.check(&TT::Identifier) && self.peek().lexeme == "in") {
+            self.report_error(self.peek(), "Expect 'in' after loop variable.");
+            return Err(ParseError::ConsumeUntilTokenNotFound);
+        }
+        self.consume();
+
+        let iterable = self.expression()?;
+        self.consume_expected(TT::RightParen, "Expect ')' after iterable.")?;
+
+        // declared apart from the enclosing scope, each iteration gets a fresh binding
+        self.scopes.push(HashMap::new());
+        self.declare(&name, false);
+        self.loop_depth += 1;
+        let body = self.nested(Self::statement);
+        self.loop_depth -= 1;
+        self.scopes.pop();
+
+        Ok(Stmt::ForIn {
+            keyword,
+            name,
+            iterable,
+            body: Box::new(body?),
+        })
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        let value = self.expression()?;
+        self.consume_expected(TT::Semicolon, "Expect ';' after thrown value.")?;
+        Ok(Stmt::Throw(keyword, value))
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+        self.consume_expected(TT::LeftBrace, "Expect '{' after 'try'.")?;
+        let body = self.nested(Self::block)?;
+
+        let mut catch = None;
+        if self.consume_matches(&[TT::Catch]) {
+            self.consume_expected(TT::LeftParen, "Expect '(' after 'catch'.")?;
+            let name = self.consume_expected(TT::Identifier, "Expect caught value name.")?;
+            self.consume_expected(TT::RightParen, "Expect ')' after caught value name.")?;
+            self.consume_expected(TT::LeftBrace, "Expect '{' after catch clause.")?;
+
+            // caught value lives in its own scope around the handler block
+            self.scopes.push(HashMap::new());
+            self.declare(&name, false);
+            let handler = self.nested(Self::block);
+            self.scopes.pop();
+
+            catch = Some((name, handler?));
+        }
+
+        let mut finally = None;
+        if self.consume_matches(&[TT::Finally]) {
+            self.consume_expected(TT::LeftBrace, "Expect '{' after 'finally'.")?;
+            finally = Some(self.nested(Self::block)?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            self.report_error(&keyword, "Expect 'catch' or 'finally' after try block.");
+        }
+
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
+    fn loop_jump_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
+
+        if self.loop_depth == 0 {
+            self.report_error(
+                &keyword,
+                &format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+            );
+        }
+
+        self.consume_expected(
+            TT::Semicolon,
+            &format!("Expect ';' after '{}'.", keyword.lexeme),
+        )?;
+
+        if keyword.token_type.variant_eq(&TT::Break) {
+            Ok(Stmt::Break(keyword))
+        } else {
+            Ok(Stmt::Continue(keyword))
+        }
+    }
+
     fn print_statement(&mut self) -> Result<Stmt> {
+        let keyword = self.previous();
         let expr = self.expression()?;
         self.consume_expected(TT::Semicolon, "Expect ';' after value.")?;
-        Ok(Stmt::Print(expr))
+        Ok(Stmt::Print(keyword, expr))
     }
 
     fn expression_statement(&mut self) -> Result<Stmt> {
@@ -109,6 +415,13 @@ impl Parser {
     }
 
     fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.scopes.push(HashMap::new());
+        let statements = self.block_statements();
+        self.scopes.pop();
+        statements
+    }
+
+    fn block_statements(&mut self) -> Result<Vec<Stmt>> {
         let mut statements = Vec::new();
 
         while !self.check(&TT::RightBrace) && !self.is_at_end() {
@@ -121,21 +434,101 @@ impl Parser {
     }
 
     fn expression(&mut self) -> Result<Expr> {
-        self.assignment()
+        self.nested(Self::assignment)
     }
 
     fn assignment(&mut self) -> Result<Expr> {
-        let expr = self.equality()?;
+        let expr = self.conditional()?;
 
         if self.consume_matches(&[TT::Equal]) {
             let equals = self.previous();
-            let value = self.assignment()?;
+            let value = self.expression()?;
 
-            if let Expr::Variable(name) = expr {
-                return Ok(Expr::Assign(name, Box::new(value)));
+            match expr {
+                Expr::Variable(name) => {
+                    self.check_assignable(&name);
+                    return Ok(Expr::Assign(name, Box::new(value)));
+                }
+                Expr::List(_) => {
+                    if let Some(target) = assignment_target(&expr, &equals) {
+                        for name in target.names() {
+                            self.check_assignable(name);
+                        }
+                        return Ok(Expr::AssignDestructure(target, Box::new(value)));
+                    }
+                }
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => {
+                    return Ok(Expr::SetIndex {
+                        object,
+                        bracket,
+                        index,
+                        value: Box::new(value),
+                    })
+                }
+                _ => (),
             }
 
             self.report_error(&equals, "Invalid assignment target.");
+        } else if self.consume_matches(&[
+            TT::PlusEqual,
+            TT::MinusEqual,
+            TT::StarEqual,
+            TT::SlashEqual,
+            TT::PercentEqual,
+        ]) {
+            // `a += b` is desugared into `a = a + b`
+            let operator = self.previous();
+            let value = self.expression()?;
+
+            if let Expr::Variable(name) = expr {
+                self.check_assignable(&name);
+                let binary = Expr::Binary {
+                    left: Box::new(Expr::Variable(name.clone())),
+                    operator: arithmetic_operator(&operator),
+                    right: Box::new(value),
+                };
+                return Ok(Expr::Assign(name, Box::new(binary)));
+            }
+
+            self.report_error(&operator, "Invalid assignment target.");
+        }
+
+        Ok(expr)
+    }
+
+    // `condition ? then : else`, right-associative so that conditionals can be chained
+    fn conditional(&mut self) -> Result<Expr> {
+        let condition = self.coalesce()?;
+
+        if self.consume_matches(&[TT::Question]) {
+            let then_branch = self.expression()?;
+            self.consume_expected(TT::Colon, "Expect ':' after then branch of conditional.")?;
+            let else_branch = self.nested(Self::conditional)?;
+            return Ok(Expr::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> Result<Expr> {
+        let mut expr = self.equality()?;
+
+        while self.consume_matches(&[TT::QuestionQuestion]) {
+            let operator = self.previous();
+            let right = self.equality()?;
+            expr = Expr::Coalesce {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
         }
 
         Ok(expr)
@@ -192,7 +585,7 @@ impl Parser {
     fn factor(&mut self) -> Result<Expr> {
         let mut expr = self.unary()?;
 
-        while self.consume_matches(&[TT::Slash, TT::Star]) {
+        while self.consume_matches(&[TT::Slash, TT::Star, TT::Percent]) {
             let operator = self.previous();
             let right = self.unary()?;
             expr = Expr::Binary {
@@ -208,14 +601,126 @@ impl Parser {
     fn unary(&mut self) -> Result<Expr> {
         if self.consume_matches(&[TT::Bang, TT::Minus]) {
             let operator = self.previous();
-            let right = self.unary()?;
+            let right = self.nested(Self::unary)?;
             return Ok(Expr::Unary {
                 operator,
                 right: Box::new(right),
             });
         }
 
-        self.primary()
+        if self.consume_matches(&[TT::PlusPlus, TT::MinusMinus]) {
+            // `++a` is desugared into `a = a + 1`
+            let operator = self.previous();
+            let target = self.nested(Self::unary)?;
+            return match target {
+                Expr::Variable(name) => {
+                    self.check_assignable(&name);
+                    Ok(increment(name, &operator))
+                }
+                _ => {
+                    self.report_error(&operator, "Invalid increment target.");
+                    Ok(target)
+                }
+            };
+        }
+
+        self.power()
+    }
+
+    // `**` binds tighter than unary minus on its left, so `-2 ** 2` is `-(2 ** 2)`,
+    // and is right-associative because exponent is parsed as unary
+    fn power(&mut self) -> Result<Expr> {
+        let expr = self.postfix()?;
+
+        if self.consume_matches(&[TT::StarStar]) {
+            let operator = self.previous();
+            let right = self.nested(Self::unary)?;
+            return Ok(Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr> {
+        let expr = self.call()?;
+
+        if self.consume_matches(&[TT::PlusPlus, TT::MinusMinus]) {
+            let operator = self.previous();
+            return match expr {
+                Expr::Variable(name) => {
+                    self.check_assignable(&name);
+                    Ok(Expr::Postfix { name, operator })
+                }
+                _ => {
+                    self.report_error(&operator, "Invalid increment target.");
+                    Ok(expr)
+                }
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Expr> {
+        let mut expr = self.primary()?;
+
+        loop {
+            if self.consume_matches(&[TT::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.consume_matches(&[TT::Dot]) {
+                let name =
+                    self.consume_expected(TT::Identifier, "Expect property name after '.'.")?;
+                if let Expr::Variable(enum_name) = &expr {
+                    self.check_variant(enum_name, &name);
+                }
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else if self.consume_matches(&[TT::LeftBracket]) {
+                let bracket = self.previous();
+                let index = self.expression()?;
+                self.consume_expected(TT::RightBracket, "Expect ']' after index.")?;
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr> {
+        let mut arguments = Vec::new();
+
+        if !self.check(&TT::RightParen) {
+            loop {
+                if arguments.len() >= 255 {
+                    self.report_error(self.peek(), "Can't have more than 255 arguments.");
+                }
+                arguments.push(self.expression()?);
+
+                if !self.consume_matches(&[TT::Comma]) {
+                    break;
+                }
+            }
+        }
+
+        let paren = self.consume_expected(TT::RightParen, "Expect ')' after arguments.")?;
+
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
     }
 
     fn primary(&mut self) -> Result<Expr> {
@@ -225,25 +730,347 @@ impl Parser {
             Ok(Expr::Literal(V::Bool(true)))
         } else if self.consume_matches(&[TT::Nil]) {
             Ok(Expr::Literal(V::Nil))
-        } else if self.consume_matches(&[TT::Number(0.0), TT::String("".into())]) {
+        } else if self.consume_matches(&[TT::Number(0.0), TT::Integer(0), TT::String("".into())]) {
             let prev_token = self.previous();
             match prev_token.token_type {
                 TT::Number(num) => Ok(Expr::Literal(V::Number(num))),
+                TT::Integer(int) => Ok(Expr::Literal(V::Integer(int))),
                 TT::String(str) => Ok(Expr::Literal(V::String(str))),
                 _ => panic!("the primary value neither string nor number despite enum match"),
             }
+        } else if self.consume_matches(&[TT::Interpolation("".into())]) {
+            self.interpolation()
+        } else if self.consume_matches(&[TT::Match]) {
+            self.match_expression()
         } else if self.consume_matches(&[TT::Identifier]) {
             Ok(Expr::Variable(self.previous()))
+        } else if self.consume_matches(&[TT::LeftBracket]) {
+            let mut elements = Vec::new();
+            if !self.check(&TT::RightBracket) {
+                loop {
+                    elements.push(self.expression()?);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume_expected(TT::RightBracket, "Expect ']' after list elements.")?;
+            Ok(Expr::List(elements))
+        } else if self.consume_matches(&[TT::LeftBrace]) {
+            // braces at the start of a statement are parsed as a block before reaching here,
+            // so in expression position they are always a map literal
+            let brace = self.previous();
+            let mut entries = Vec::new();
+            if !self.check(&TT::RightBrace) {
+                loop {
+                    let key = self.expression()?;
+                    self.consume_expected(TT::Colon, "Expect ':' after map key.")?;
+                    let value = self.expression()?;
+                    entries.push((key, value));
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume_expected(TT::RightBrace, "Expect '}' after map entries.")?;
+            Ok(Expr::Map { brace, entries })
         } else if self.consume_matches(&[TT::LeftParen]) {
             let expr = self.expression()?;
             self.consume_expected(TT::RightParen, "Expect ')' after expression")?;
-            return Ok(Expr::Grouping(Box::new(expr)));
+            Ok(Expr::Grouping(Box::new(expr)))
         } else {
             self.report_error(self.peek(), "Expect expression.");
             Err(ParseError::ExpectExpression)
         }
     }
 
+    fn match_expression(&mut self) -> Result<Expr> {
+        let keyword = self.previous();
+        self.consume_expected(TT::LeftParen, "Expect '(' after 'match'.")?;
+        let value = self.expression()?;
+        self.consume_expected(TT::RightParen, "Expect ')' after matched value.")?;
+        self.consume_expected(TT::LeftBrace, "Expect '{' before match arms.")?;
+
+        let mut arms = Vec::new();
+        let mut reachable = true;
+        while !self.check(&TT::RightBrace) && !self.is_at_end() {
+            let start = self.peek().clone();
+
+            // bindings of a pattern are only visible in its guard and body
+            self.scopes.push(HashMap::new());
+            let arm = self.match_arm();
+            self.scopes.pop();
+            let arm = arm?;
+
+            if !reachable {
+                self.report_warning(&start, "Unreachable match arm.");
+            }
+            if arm.guard.is_none() && arm.pattern.is_irrefutable() {
+                reachable = false;
+            }
+            arms.push(arm);
+
+            if !self.consume_matches(&[TT::Comma]) {
+                break;
+            }
+        }
+        self.consume_expected(TT::RightBrace, "Expect '}' after match arms.")?;
+
+        Ok(Expr::Match {
+            keyword,
+            value: Box::new(value),
+            arms,
+        })
+    }
+
+    fn match_arm(&mut self) -> Result<MatchArm> {
+        let pattern = self.pattern()?;
+        let guard = if self.consume_matches(&[TT::If]) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume_expected(TT::EqualGreater, "Expect '=>' after pattern.")?;
+        let body = self.expression()?;
+
+        Ok(MatchArm {
+            pattern,
+            guard,
+            body,
+        })
+    }
+
+    fn pattern(&mut self) -> Result<Pattern> {
+        let mut alternatives = vec![self.single_pattern()?];
+        while self.consume_matches(&[TT::Pipe]) {
+            alternatives.push(self.single_pattern()?);
+        }
+
+        // whichever alternative matches, the arm sees the same names
+        let names: Vec<Vec<&Token>> = alternatives.iter().map(Pattern::names).collect();
+        let unbound = names.iter().flatten().find(|name| {
+            !names
+                .iter()
+                .all(|other| other.iter().any(|other| other.lexeme == name.lexeme))
+        });
+        if let Some(name) = unbound {
+            self.report_error(
+                name,
+                &format!(
+                    "Name '{}' must be bound in every alternative of the pattern.",
+                    name.lexeme
+                ),
+            );
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives.remove(0))
+        } else {
+            Ok(Pattern::Alternatives(alternatives))
+        }
+    }
+
+    fn single_pattern(&mut self) -> Result<Pattern> {
+        if self.consume_matches(&[TT::False]) {
+            Ok(Pattern::Literal(V::Bool(false)))
+        } else if self.consume_matches(&[TT::True]) {
+            Ok(Pattern::Literal(V::Bool(true)))
+        } else if self.consume_matches(&[TT::Nil]) {
+            Ok(Pattern::Literal(V::Nil))
+        } else if self.consume_matches(&[TT::Number(0.0), TT::Integer(0), TT::String("".into())]) {
+            match self.previous().token_type {
+                TT::Number(num) => Ok(Pattern::Literal(V::Number(num))),
+                TT::Integer(int) => Ok(Pattern::Literal(V::Integer(int))),
+                TT::String(str) => Ok(Pattern::Literal(V::String(str))),
+                _ => panic!("the pattern literal neither string nor number despite enum match"),
+            }
+        } else if self.consume_matches(&[TT::Minus]) {
+            if !self.consume_matches(&[TT::Number(0.0), TT::Integer(0)]) {
+                self.report_error(self.peek(), "Expect number after '-' in pattern.");
+                return Err(ParseError::ConsumeUntilTokenNotFound);
+            }
+            match self.previous().token_type {
+                TT::Number(num) => Ok(Pattern::Literal(V::Number(-num))),
+                TT::Integer(int) => Ok(Pattern::Literal(V::Integer(-int))),
+                _ => panic!("the negated pattern is not a number despite enum match"),
+            }
+        } else if self.check(&TT::Identifier) && self.check_next(&TT::Dot) {
+            self.variant_pattern()
+        } else if self.consume_matches(&[TT::Identifier]) {
+            let name = self.previous();
+            if name.lexeme == "_" {
+                return Ok(Pattern::Wildcard);
+            }
+            self.declare(&name, false);
+            Ok(Pattern::Binding(name))
+        } else if self.consume_matches(&[TT::LeftBracket]) {
+            let mut elements = Vec::new();
+            if !self.check(&TT::RightBracket) {
+                loop {
+                    elements.push(self.nested(Self::pattern)?);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            self.consume_expected(TT::RightBracket, "Expect ']' after list pattern.")?;
+            Ok(Pattern::List(elements))
+        } else {
+            self.report_error(self.peek(), "Expect pattern.");
+            Err(ParseError::ExpectExpression)
+        }
+    }
+
+    fn variant_pattern(&mut self) -> Result<Pattern> {
+        let enum_name = self.consume();
+        self.consume();
+        let variant = self.consume_expected(TT::Identifier, "Expect variant name after '.'.")?;
+        let fields = self.check_variant(&enum_name, &variant);
+
+        let mut payload = None;
+        if self.consume_matches(&[TT::LeftParen]) {
+            let mut elements = Vec::new();
+            if !self.check(&TT::RightParen) {
+                loop {
+                    elements.push(self.nested(Self::pattern)?);
+                    if !self.consume_matches(&[TT::Comma]) {
+                        break;
+                    }
+                }
+            }
+            let paren =
+                self.consume_expected(TT::RightParen, "Expect ')' after variant payload.")?;
+            if fields.is_some_and(|fields| fields != elements.len()) {
+                self.report_error(
+                    &paren,
+                    &format!(
+                        "Variant '{}' has {} fields but the pattern has {}.",
+                        variant.lexeme,
+                        fields.unwrap_or_default(),
+                        elements.len()
+                    ),
+                );
+            }
+            payload = Some(elements);
+        }
+
+        Ok(Pattern::Variant {
+            enum_name,
+            variant,
+            payload,
+        })
+    }
+
+    fn declare(&mut self, name: &Token, constant: bool) {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("global scope must always be present");
+        let declaration = Declaration {
+            constant,
+            variants: None,
+        };
+        let was_constant = scope
+            .insert(name.lexeme.to_owned(), declaration)
+            .is_some_and(|previous| previous.constant);
+
+        if was_constant {
+            self.report_error(
+                name,
+                &format!("Already a constant named '{}' in this scope.", name.lexeme),
+            );
+        }
+    }
+
+    // names declared somewhere else, e.g. by `import`, are unknown here and checked at runtime
+    fn check_assignable(&self, name: &Token) {
+        let constant = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.lexeme))
+            .is_some_and(|declaration| declaration.constant);
+
+        if constant {
+            self.report_error(
+                name,
+                &format!("Can't assign to constant '{}'.", name.lexeme),
+            );
+        }
+    }
+
+    // only enums declared in this file are known, others are checked at runtime.
+    // returns the field count of the variant when it is known
+    fn check_variant(&self, enum_name: &Token, variant: &Token) -> Option<usize> {
+        let variants = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&enum_name.lexeme))
+            .and_then(|declaration| declaration.variants.as_ref())?;
+
+        let fields = variants
+            .iter()
+            .find(|(name, _)| *name == variant.lexeme)
+            .map(|(_, fields)| *fields);
+        if fields.is_none() {
+            self.report_error(
+                variant,
+                &format!(
+                    "Enum '{}' has no variant '{}'.",
+                    enum_name.lexeme, variant.lexeme
+                ),
+            );
+        }
+        fields
+    }
+
+    // parser recurses natively, so nesting is bounded to not overflow the stack
+    fn nested<T>(&mut self, parse: fn(&mut Self) -> Result<T>) -> Result<T> {
+        if self.max_nesting.is_some_and(|max| self.depth >= max) {
+            self.report_error(self.peek(), "Too deeply nested.");
+            return Err(ParseError::TooDeeplyNested);
+        }
+
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    // scanner splits `"a ${x} b ${y} c"` into Interpolation("a "), x, Interpolation(" b "), y, String(" c")
+    fn interpolation(&mut self) -> Result<Expr> {
+        let mut parts = Vec::new();
+        let mut token = self.previous();
+
+        loop {
+            match token.token_type {
+                TT::Interpolation(text) => {
+                    if !text.is_empty() {
+                        parts.push(Expr::Literal(V::String(text)));
+                    }
+                    parts.push(self.expression()?);
+                }
+                TT::String(text) => {
+                    if !text.is_empty() {
+                        parts.push(Expr::Literal(V::String(text)));
+                    }
+                    return Ok(Expr::Interpolation(parts));
+                }
+                _ => panic!("the interpolation part is not a string despite enum match"),
+            }
+
+            if self.consume_matches(&[TT::Interpolation("".into())]) {
+                token = self.previous();
+            } else {
+                token = self.consume_expected(
+                    TT::String("".into()),
+                    "Expect '}' after interpolated expression.",
+                )?;
+            }
+        }
+    }
+
     fn consume_matches(&mut self, types: &[TokenType]) -> bool {
         for token_type in types {
             if self.check(token_type) {
@@ -272,6 +1099,12 @@ impl Parser {
         self.peek().token_type.variant_eq(token_type)
     }
 
+    fn check_next(&self, token_type: &TokenType) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|token| token.token_type.variant_eq(token_type))
+    }
+
     fn consume(&mut self) -> Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -306,12 +1139,17 @@ impl Parser {
 
             match self.peek().token_type {
                 TT::Class
+                | TT::Enum
                 | TT::Fun
                 | TT::Var
+                | TT::Const
+                | TT::Import
                 | TT::For
                 | TT::If
                 | TT::While
                 | TT::Print
+                | TT::Throw
+                | TT::Try
                 | TT::Return => {
                     return;
                 }
@@ -322,3 +1160,43 @@ impl Parser {
         }
     }
 }
+
+// `+=` into `+` and alike, keeping position of the original token for errors
+fn arithmetic_operator(compound: &Token) -> Token {
+    let token_type = match compound.token_type {
+        TT::PlusEqual | TT::PlusPlus => TT::Plus,
+        TT::MinusEqual | TT::MinusMinus => TT::Minus,
+        TT::StarEqual => TT::Star,
+        TT::SlashEqual => TT::Slash,
+        TT::PercentEqual => TT::Percent,
+        _ => panic!("the operator is not a compound assignment despite enum match"),
+    };
+    Token::new(token_type, compound.lexeme[..1].to_owned(), compound.line)
+}
+
+fn increment(name: Token, operator: &Token) -> Expr {
+    Expr::Assign(
+        name.clone(),
+        Box::new(Expr::Binary {
+            left: Box::new(Expr::Variable(name)),
+            operator: arithmetic_operator(operator),
+            right: Box::new(Expr::Literal(V::Integer(1))),
+        }),
+    )
+}
+
+// `[a, [b, c]]` on the left of `=` is parsed as a list literal first, then
+// turned into a target when it only contains variables, errors point at the `=`
+fn assignment_target(expr: &Expr, equals: &Token) -> Option<Destructure> {
+    match expr {
+        Expr::Variable(name) => Some(Destructure::Name(name.clone())),
+        Expr::List(elements) => {
+            let targets = elements
+                .iter()
+                .map(|element| assignment_target(element, equals))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Destructure::List(equals.clone(), targets))
+        }
+        _ => None,
+    }
+}
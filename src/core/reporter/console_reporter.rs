@@ -1,7 +1,4 @@
-use std::{
-    cell::{Cell, RefCell},
-    rc::Rc,
-};
+use std::{cell::Cell, rc::Rc};
 
 use super::ErrorReporter;
 
@@ -10,6 +7,7 @@ use super::ErrorReporter;
 pub struct ConsoleReporter {
     had_error: Cell<bool>,
     had_runtime_error: Cell<bool>,
+    was_cancelled: Cell<bool>,
 }
 
 impl ConsoleReporter {
@@ -24,6 +22,10 @@ impl ConsoleReporter {
     pub fn is_had_runtime_error(&self) -> bool {
         self.had_runtime_error.get()
     }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.was_cancelled.get()
+    }
 }
 
 impl ErrorReporter for ConsoleReporter {
@@ -39,9 +41,19 @@ impl ErrorReporter for ConsoleReporter {
         self.had_error.set(true);
     }
 
-    fn report_runtime(&self, line: usize, message: &str) {
-        eprintln!("{}\n[line {}]", message, line);
+    fn report_warning(&self, line: usize, where_occurred: &str, message: &str) {
+        eprintln!("[line {line}] Warning{where_occurred}: {message}");
+    }
+
+    fn report_runtime(&self, line: usize, where_occurred: &str, message: &str) {
+        eprintln!("{}\n[line {}{}]", message, line, where_occurred);
 
         self.had_runtime_error.set(true);
     }
+
+    fn report_cancelled(&self, line: usize) {
+        eprintln!("Execution cancelled.\n[line {}]", line);
+
+        self.was_cancelled.set(true);
+    }
 }
@@ -1,4 +1,8 @@
 pub trait ErrorReporter {
     fn report(&self, line: usize, where_occurred: &str, message: &str);
-    fn report_runtime(&self, line: usize, message: &str);
+    // problem that doesn't stop the script from running
+    fn report_warning(&self, line: usize, where_occurred: &str, message: &str);
+    fn report_runtime(&self, line: usize, where_occurred: &str, message: &str);
+    // script was stopped by the host, which is not a failure of the script itself
+    fn report_cancelled(&self, line: usize);
 }
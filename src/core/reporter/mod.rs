@@ -1,3 +1,4 @@
 pub mod console_reporter;
 pub mod error_reporter;
+pub mod module_reporter;
 pub use error_reporter::*;
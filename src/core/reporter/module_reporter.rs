@@ -0,0 +1,57 @@
+use std::{cell::Cell, rc::Rc};
+
+use super::ErrorReporter;
+
+// forwards errors of an imported module to the main reporter, prefixed with the module path.
+// lexemes of syntax errors are left out, so importing a file that is not lox
+// doesn't echo pieces of it
+pub struct ModuleReporter {
+    inner: Option<Rc<dyn ErrorReporter>>,
+    module: String,
+    had_error: Cell<bool>,
+}
+
+impl ModuleReporter {
+    pub fn new(inner: Option<Rc<dyn ErrorReporter>>, module: &str) -> Rc<Self> {
+        Rc::new(ModuleReporter {
+            inner,
+            module: module.to_owned(),
+            had_error: Cell::new(false),
+        })
+    }
+
+    pub fn is_had_error(&self) -> bool {
+        self.had_error.get()
+    }
+}
+
+impl ErrorReporter for ModuleReporter {
+    fn report(&self, line: usize, _: &str, message: &str) {
+        if let Some(inner) = &self.inner {
+            inner.report(line, &format!(" in {}", self.module), message);
+        }
+        self.had_error.set(true);
+    }
+
+    fn report_warning(&self, line: usize, _: &str, message: &str) {
+        if let Some(inner) = &self.inner {
+            inner.report_warning(line, &format!(" in {}", self.module), message);
+        }
+    }
+
+    fn report_runtime(&self, line: usize, where_occurred: &str, message: &str) {
+        if let Some(inner) = &self.inner {
+            inner.report_runtime(
+                line,
+                &format!(" in {}{}", self.module, where_occurred),
+                message,
+            );
+        }
+    }
+
+    fn report_cancelled(&self, line: usize) {
+        if let Some(inner) = &self.inner {
+            inner.report_cancelled(line);
+        }
+    }
+}
@@ -4,11 +4,15 @@ use std::{char, collections::HashMap, mem, rc::Rc};
 use super::reporter::ErrorReporter;
 
 pub struct Scanner {
-    source: String,
+    // chars instead of a string so that positions are char based and non-ascii source is fine
+    source: Vec<char>,
     tokens: Vec<Token>,
     start: usize,
     current: usize,
     line: usize,
+    // one entry per string interpolation being scanned, counting braces opened inside of it
+    // to find the '}' which closes the interpolation
+    interpolations: Vec<usize>,
     reporter: Option<Rc<dyn ErrorReporter>>,
 }
 
@@ -16,19 +20,29 @@ impl Scanner {
     fn get_keywords() -> HashMap<&'static str, TokenType> {
         HashMap::from([
             ("and", TokenType::And),
+            ("break", TokenType::Break),
+            ("catch", TokenType::Catch),
             ("class", TokenType::Class),
+            ("const", TokenType::Const),
+            ("continue", TokenType::Continue),
             ("else", TokenType::Else),
+            ("enum", TokenType::Enum),
             ("false", TokenType::False),
+            ("finally", TokenType::Finally),
             ("for", TokenType::For),
             ("fun", TokenType::Fun),
             ("if", TokenType::If),
+            ("import", TokenType::Import),
+            ("match", TokenType::Match),
             ("nil", TokenType::Nil),
             ("or", TokenType::Or),
             ("print", TokenType::Print),
             ("return", TokenType::Return),
             ("super", TokenType::Super),
             ("this", TokenType::This),
+            ("throw", TokenType::Throw),
             ("true", TokenType::True),
+            ("try", TokenType::Try),
             ("var", TokenType::Var),
             ("while", TokenType::While),
         ])
@@ -36,11 +50,12 @@ impl Scanner {
 
     pub fn new(source: &str) -> Self {
         Self {
-            source: source.to_string(),
+            source: source.chars().collect(),
             tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            interpolations: Vec::new(),
             reporter: None,
         }
     }
@@ -65,6 +80,10 @@ impl Scanner {
             self.scan_token()
         }
 
+        if !self.interpolations.is_empty() {
+            self.report_error(self.line, "Unterminated string interpolation.");
+        }
+
         self.tokens.push(Token {
             token_type: TokenType::EndOfFile,
             lexeme: "".to_owned(),
@@ -80,23 +99,69 @@ impl Scanner {
         match consumed_char {
             '(' => self.add_token(TokenType::LeftParen),
             ')' => self.add_token(TokenType::RightParen),
-            '{' => self.add_token(TokenType::LeftBrace),
-            '}' => self.add_token(TokenType::RightBrace),
+            '{' => {
+                if let Some(braces) = self.interpolations.last_mut() {
+                    *braces += 1;
+                }
+                self.add_token(TokenType::LeftBrace)
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(0) => {
+                    // end of interpolated expression, the rest is string again
+                    self.interpolations.pop();
+                    self.consume_string();
+                }
+                Some(braces) => {
+                    *braces -= 1;
+                    self.add_token(TokenType::RightBrace)
+                }
+                None => self.add_token(TokenType::RightBrace),
+            },
+            '[' => self.add_token(TokenType::LeftBracket),
+            ']' => self.add_token(TokenType::RightBracket),
             ',' => self.add_token(TokenType::Comma),
+            ':' => self.add_token(TokenType::Colon),
+            '|' => self.add_token(TokenType::Pipe),
+            '?' => self.add_token_with_match('?', TokenType::QuestionQuestion, TokenType::Question),
             '.' => self.add_token(TokenType::Dot),
-            '-' => self.add_token(TokenType::Minus),
-            '+' => self.add_token(TokenType::Plus),
+            '-' => {
+                if self.next_matches('-') {
+                    self.add_token(TokenType::MinusMinus)
+                } else {
+                    self.add_token_with_match('=', TokenType::MinusEqual, TokenType::Minus)
+                }
+            }
+            '+' => {
+                if self.next_matches('+') {
+                    self.add_token(TokenType::PlusPlus)
+                } else {
+                    self.add_token_with_match('=', TokenType::PlusEqual, TokenType::Plus)
+                }
+            }
             ';' => self.add_token(TokenType::Semicolon),
-            '*' => self.add_token(TokenType::Star),
+            '*' => {
+                if self.next_matches('*') {
+                    self.add_token(TokenType::StarStar)
+                } else {
+                    self.add_token_with_match('=', TokenType::StarEqual, TokenType::Star)
+                }
+            }
+            '%' => self.add_token_with_match('=', TokenType::PercentEqual, TokenType::Percent),
             '!' => self.add_token_with_match('=', TokenType::BangEqual, TokenType::Bang),
-            '=' => self.add_token_with_match('=', TokenType::EqualEqual, TokenType::Equal),
+            '=' => {
+                if self.next_matches('>') {
+                    self.add_token(TokenType::EqualGreater)
+                } else {
+                    self.add_token_with_match('=', TokenType::EqualEqual, TokenType::Equal)
+                }
+            }
             '<' => self.add_token_with_match('=', TokenType::LessEqual, TokenType::Less),
             '>' => self.add_token_with_match('=', TokenType::GreaterEqual, TokenType::Greater),
             '/' => {
                 if self.next_matches('/') {
                     self.consume_comment();
                 } else {
-                    self.add_token(TokenType::Slash)
+                    self.add_token_with_match('=', TokenType::SlashEqual, TokenType::Slash)
                 }
             }
             '\n' => {
@@ -104,12 +169,13 @@ impl Scanner {
             }
             ' ' | '\r' | '\t' => (),
             '"' => self.consume_string(),
+            '`' => self.consume_raw_string(),
             _ => {
                 // println!("consume char {}", consumed_char);
 
                 if consumed_char.is_ascii_digit() {
                     self.consume_number();
-                } else if consumed_char.is_alphabetic() {
+                } else if consumed_char.is_alphabetic() || consumed_char == '_' {
                     self.consume_identifier();
                 } else {
                     self.report_error(self.line, "unexpected char") // todo: what I was going to fix?
@@ -120,20 +186,118 @@ impl Scanner {
     }
 
     fn consume_identifier(&mut self) {
-        while self.peek().is_alphanumeric() {
+        while self.peek().is_alphanumeric() || self.peek() == '_' {
             self.consume();
         }
 
-        let text = &self.source[self.start..self.current];
-        if let Some(token_type) = Scanner::get_keywords().get(text).cloned() {
+        let text = self.text(self.start, self.current);
+        if let Some(token_type) = Scanner::get_keywords().get(text.as_str()).cloned() {
             self.add_token(token_type);
         } else {
             self.add_token(TokenType::Identifier);
         }
     }
 
+    // scans string contents up to the closing quote, or up to `${` which starts
+    // interpolated expression, then the string continues after the matching '}'
     fn consume_string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
+        let mut value = String::new();
+
+        loop {
+            if self.is_at_end() {
+                self.report_error(self.line, "Unterminated string.");
+                return;
+            }
+
+            match self.consume().expect("Failed to consume next character") {
+                '"' => break,
+                '$' if self.peek() == '{' => {
+                    self.consume();
+                    self.interpolations.push(0);
+                    self.add_token(TokenType::Interpolation(value));
+                    return;
+                }
+                '\\' => {
+                    if let Some(escaped) = self.consume_escape() {
+                        value.push(escaped);
+                    }
+                }
+                consumed_char => {
+                    if consumed_char == '\n' {
+                        self.line += 1;
+                    }
+                    value.push(consumed_char);
+                }
+            }
+        }
+
+        self.add_token(TokenType::String(value));
+    }
+
+    fn consume_escape(&mut self) -> Option<char> {
+        let escaped = match self.consume() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('$') => '$',
+            Some('u') => return self.consume_unicode_escape(),
+            Some(other) => {
+                if other == '\n' {
+                    self.line += 1;
+                }
+                self.report_error(
+                    self.line,
+                    &format!("Invalid escape sequence '\\{}'.", other.escape_default()),
+                );
+                return None;
+            }
+            None => return None,
+        };
+        Some(escaped)
+    }
+
+    // `\u{...}` with 1 to 6 hex digits of a unicode scalar value
+    fn consume_unicode_escape(&mut self) -> Option<char> {
+        if !self.next_matches('{') {
+            self.report_error(self.line, "Expect '{' after '\\u' in unicode escape.");
+            return None;
+        }
+
+        let digits_start = self.current;
+        while self.peek().is_ascii_hexdigit() {
+            self.consume();
+        }
+        let digits = self.text(digits_start, self.current);
+
+        if !self.next_matches('}') {
+            self.report_error(self.line, "Expect '}' after unicode escape digits.");
+            return None;
+        }
+        if digits.is_empty() || digits.len() > 6 {
+            self.report_error(
+                self.line,
+                &format!("Unicode escape '\\u{{{digits}}}' must have 1 to 6 hex digits."),
+            );
+            return None;
+        }
+
+        let code = u32::from_str_radix(&digits, 16).expect("Consumed digits are not hex");
+        let escaped = char::from_u32(code);
+        if escaped.is_none() {
+            self.report_error(
+                self.line,
+                &format!("Unicode escape '\\u{{{digits}}}' is not a valid character."),
+            );
+        }
+        escaped
+    }
+
+    // backtick strings are taken as is: they may span lines, and have no escapes or interpolation
+    fn consume_raw_string(&mut self) {
+        while self.peek() != '`' && !self.is_at_end() {
             if self.peek() == '\n' {
                 self.line += 1;
             }
@@ -141,36 +305,128 @@ impl Scanner {
         }
 
         if self.is_at_end() {
-            self.report_error(self.line, "Unterminated string.");
+            self.report_error(self.line, "Unterminated raw string.");
             return;
         }
 
-        // consume the closing "
+        // consume the closing `
         self.consume();
 
-        let value = self.source[self.start + 1..self.current - 1].to_owned();
-
+        let value = self.text(self.start + 1, self.current - 1);
         self.add_token(TokenType::String(value));
     }
 
+    // decimal `1_000.5e-3`, hex `0xFF` and binary `0b1010` literals,
+    // the ones without fraction or exponent are integers
     fn consume_number(&mut self) {
-        while self.peek().is_ascii_digit() {
-            self.consume();
+        // rescan from the first digit, so it's validated along with the rest
+        self.current = self.start;
+
+        let result = if self.peek() == '0' && matches!(self.peek_next(), 'x' | 'X') {
+            self.current += 2;
+            self.consume_radix_number(16)
+        } else if self.peek() == '0' && matches!(self.peek_next(), 'b' | 'B') {
+            self.current += 2;
+            self.consume_radix_number(2)
+        } else {
+            self.consume_decimal_number()
+        };
+
+        // letters or digits glued to the literal, e.g. `0b102` or `12abc`, make it malformed
+        let result = if self.peek().is_alphanumeric() || self.peek() == '_' {
+            while self.peek().is_alphanumeric() || self.peek() == '_' {
+                self.consume();
+            }
+            Err("Invalid character")
+        } else {
+            result
+        };
+
+        match result {
+            Ok(token_type) => self.add_token(token_type),
+            Err(problem) => {
+                let literal = self.text(self.start, self.current);
+                self.report_error(
+                    self.line,
+                    &format!("{problem} in number literal '{literal}'."),
+                );
+            }
         }
+    }
+
+    fn consume_decimal_number(&mut self) -> Result<TokenType, &'static str> {
+        let mut literal = self.consume_digits(10)?;
+        let mut is_integer = true;
 
         if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.consume();
+            is_integer = false;
+            literal.push('.');
+            literal.push_str(&self.consume_digits(10)?);
+        }
 
-            while self.peek().is_ascii_digit() {
-                self.consume();
+        if matches!(self.peek(), 'e' | 'E') {
+            self.consume();
+            is_integer = false;
+            literal.push('e');
+            if matches!(self.peek(), '+' | '-') {
+                literal.push(self.consume().expect("Failed to consume exponent sign"));
+            }
+            let exponent = self.consume_digits(10)?;
+            if exponent.is_empty() {
+                return Err("Missing exponent digits");
+            }
+            literal.push_str(&exponent);
+        }
+
+        if is_integer {
+            literal
+                .parse()
+                .map(TokenType::Integer)
+                .map_err(|_| "Too large value")
+        } else {
+            literal
+                .parse()
+                .map(TokenType::Number)
+                .map_err(|_| "Invalid number")
+        }
+    }
+
+    fn consume_radix_number(&mut self, radix: u32) -> Result<TokenType, &'static str> {
+        let digits = self.consume_digits(radix)?;
+        if digits.is_empty() {
+            return Err("Missing digits");
+        }
+
+        i64::from_str_radix(&digits, radix)
+            .map(TokenType::Integer)
+            .map_err(|_| "Too large value")
+    }
+
+    // digits of the given radix, where single underscores may separate them
+    fn consume_digits(&mut self, radix: u32) -> Result<String, &'static str> {
+        let mut digits = String::new();
+        let mut misplaced_underscore = false;
+
+        loop {
+            let next_char = self.peek();
+            if next_char.is_digit(radix) {
+                digits.push(next_char);
+            } else if next_char == '_' {
+                if digits.is_empty() || !self.peek_next().is_digit(radix) {
+                    misplaced_underscore = true;
+                }
+            } else {
+                break;
             }
+            self.consume();
         }
 
-        let number_literal = self.source[self.start..self.current].to_owned();
-        let value: f64 = number_literal
-            .parse()
-            .expect("Consumed string is not a number");
-        self.add_token(TokenType::Number(value));
+        if misplaced_underscore {
+            Err("Misplaced underscore")
+        } else {
+            Ok(digits)
+        }
     }
 
     fn consume_comment(&mut self) {
@@ -193,46 +449,38 @@ impl Scanner {
     }
 
     fn next_matches(&mut self, expected: char) -> bool {
-        if self.is_at_end() {
-            return false;
-        };
-
-        match self.source.chars().nth(self.current) {
-            Some(next_char) => {
-                if next_char == expected {
-                    self.current += 1;
-                    true
-                } else {
-                    false
-                }
-            }
-            None => false,
+        if self.peek() == expected && !self.is_at_end() {
+            self.current += 1;
+            true
+        } else {
+            false
         }
     }
 
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.chars().count()
+        self.current >= self.source.len()
     }
 
     fn peek(&self) -> char {
-        if self.is_at_end() {
-            return '\0';
-        }
-        self.source.chars().nth(self.current).unwrap_or('\0')
+        self.source.get(self.current).copied().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        self.source.chars().nth(self.current + 1).unwrap_or('\0')
+        self.source.get(self.current + 1).copied().unwrap_or('\0')
     }
 
     fn consume(&mut self) -> Option<char> {
-        let consumed_char = self.source.chars().nth(self.current);
+        let consumed_char = self.source.get(self.current).copied();
         self.current += 1;
         consumed_char
     }
 
+    fn text(&self, start: usize, end: usize) -> String {
+        self.source[start..end].iter().collect()
+    }
+
     fn add_token(&mut self, token_type: TokenType) {
-        let text = self.source[self.start..self.current].to_owned();
+        let text = self.text(self.start, self.current);
 
         self.tokens.push(Token {
             token_type,
@@ -7,44 +7,73 @@ pub enum TokenType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
+    Colon,
     Dot,
     Minus,
     Plus,
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Question,
+    Pipe,
 
     // One or two character tokens.
     Bang,
     BangEqual,
     Equal,
     EqualEqual,
+    EqualGreater,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
+    MinusEqual,
+    MinusMinus,
+    PlusEqual,
+    PlusPlus,
+    SlashEqual,
+    StarEqual,
+    StarStar,
+    PercentEqual,
+    QuestionQuestion,
 
     // Literals.
     Identifier,
     String(String),
+    // string part before `${`, the interpolated expression tokens follow it
+    Interpolation(String),
     Number(f64),
+    Integer(i64),
 
     // Keywords.
     And,
+    Break,
+    Catch,
     Class,
+    Const,
+    Continue,
     Else,
+    Enum,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Import,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -0,0 +1,97 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, BufRead, Read, StdinLock, Write},
+    rc::Rc,
+};
+
+use serde_json::{json, Value as Json};
+
+// Debug Adapter Protocol messages framed with `Content-Length` header over stdio
+pub struct Connection {
+    reader: RefCell<StdinLock<'static>>,
+    seq: Cell<i64>,
+}
+
+impl Connection {
+    pub fn new() -> Rc<Self> {
+        Rc::new(Connection {
+            reader: RefCell::new(io::stdin().lock()),
+            seq: Cell::new(1),
+        })
+    }
+
+    // returns None when the client closed the stream or sent malformed message
+    pub fn read_message(&self) -> Option<Json> {
+        let mut reader = self.reader.borrow_mut();
+        let mut content_length = None;
+
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).ok()? == 0 {
+                return None;
+            }
+
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length:") {
+                content_length = length.trim().parse::<usize>().ok();
+            }
+        }
+
+        let mut content = vec![0; content_length?];
+        reader.read_exact(&mut content).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub fn send_response(&self, request: &Json, body: Json) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    pub fn send_error_response(&self, request: &Json, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    pub fn send_event(&self, event: &str, body: Json) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    pub fn send_output(&self, category: &str, output: &str) {
+        self.send_event(
+            "output",
+            json!({ "category": category, "output": format!("{output}\n") }),
+        );
+    }
+
+    fn send(&self, mut message: Json) {
+        message["seq"] = json!(self.seq.get());
+        self.seq.set(self.seq.get() + 1);
+
+        let content = message.to_string();
+        let mut stdout = io::stdout().lock();
+        let _ = write!(
+            stdout,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        );
+        let _ = stdout.flush();
+    }
+}
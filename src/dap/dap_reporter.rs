@@ -0,0 +1,60 @@
+use std::{cell::Cell, rc::Rc};
+
+use crate::reporter::ErrorReporter;
+
+use super::connection::Connection;
+
+// same as ConsoleReporter, but errors are sent to the client as stderr output events
+pub struct DapReporter {
+    connection: Rc<Connection>,
+    had_error: Cell<bool>,
+    had_runtime_error: Cell<bool>,
+}
+
+impl DapReporter {
+    pub fn new(connection: Rc<Connection>) -> Rc<Self> {
+        Rc::new(DapReporter {
+            connection,
+            had_error: Cell::new(false),
+            had_runtime_error: Cell::new(false),
+        })
+    }
+
+    pub fn is_had_error(&self) -> bool {
+        self.had_error.get()
+    }
+
+    pub fn is_had_runtime_error(&self) -> bool {
+        self.had_runtime_error.get()
+    }
+}
+
+impl ErrorReporter for DapReporter {
+    fn report(&self, line: usize, where_occurred: &str, message: &str) {
+        self.connection.send_output(
+            "stderr",
+            &format!("[line {line}] Error{where_occurred}: {message}"),
+        );
+        self.had_error.set(true);
+    }
+
+    fn report_warning(&self, line: usize, where_occurred: &str, message: &str) {
+        self.connection.send_output(
+            "console",
+            &format!("[line {line}] Warning{where_occurred}: {message}"),
+        );
+    }
+
+    fn report_runtime(&self, line: usize, where_occurred: &str, message: &str) {
+        self.connection.send_output(
+            "stderr",
+            &format!("{}\n[line {}{}]", message, line, where_occurred),
+        );
+        self.had_runtime_error.set(true);
+    }
+
+    fn report_cancelled(&self, line: usize) {
+        self.connection
+            .send_output("console", &format!("Execution cancelled.\n[line {}]", line));
+    }
+}
@@ -0,0 +1,81 @@
+mod connection;
+mod dap_reporter;
+mod session;
+
+use std::{fs, path::Path, rc::Rc};
+
+use serde_json::json;
+
+use crate::{interpreter::Interpreter, parser::Parser, scanner::Scanner};
+
+use connection::Connection;
+use dap_reporter::DapReporter;
+use session::{Flow, Session};
+
+// Debug Adapter Protocol server over stdio, started with `rlox dap`
+pub fn run_dap() {
+    let connection = Connection::new();
+    let mut session = Session::new(Rc::clone(&connection));
+
+    // configuration phase, the program starts once client sends `configurationDone`
+    loop {
+        let Some(request) = connection.read_message() else {
+            return;
+        };
+        if let Flow::Resume = session.handle_request(&request) {
+            break;
+        }
+    }
+
+    let exit_code = match session
+        .program()
+        .map(|program| (program.to_owned(), fs::read_to_string(program)))
+    {
+        Some((program, Ok(source))) => run_debugged(&connection, session, &program, &source),
+        _ => {
+            connection.send_output("stderr", "Error reading a file");
+            66
+        }
+    };
+
+    connection.send_event("exited", json!({ "exitCode": exit_code }));
+    connection.send_event("terminated", json!({}));
+
+    while let Some(request) = connection.read_message() {
+        if request["command"] == "disconnect" {
+            connection.send_response(&request, json!({}));
+            return;
+        }
+        connection.send_error_response(&request, "Program has terminated.");
+    }
+}
+
+fn run_debugged(connection: &Rc<Connection>, session: Session, program: &str, source: &str) -> i32 {
+    let reporter = DapReporter::new(Rc::clone(connection));
+
+    let mut scanner = Scanner::new(source).attach_reporter(Rc::clone(&reporter));
+    let tokens = scanner.scan_tokens();
+
+    let mut parser = Parser::new(tokens).attach_reporter(Rc::clone(&reporter));
+    let statements = parser.parse();
+
+    if reporter.is_had_error() {
+        return 65;
+    }
+
+    let capabilities = session.capabilities().clone();
+    let mut interpreter = Interpreter::new()
+        .attach_reporter(Rc::clone(&reporter))
+        .attach_capabilities(capabilities)
+        .attach_script_path(Path::new(program))
+        .attach_debugger(session);
+    if let Some(code) = interpreter.interpret(&statements) {
+        return code;
+    }
+
+    if reporter.is_had_runtime_error() {
+        70
+    } else {
+        0
+    }
+}
@@ -0,0 +1,400 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    process,
+    rc::Rc,
+};
+
+use serde_json::{json, Value as Json};
+
+use crate::{
+    ast::Stmt, builtins::Capability, debugger::DebugHook, environment::Environment, parser::Parser,
+    scanner::Scanner,
+};
+
+use super::connection::Connection;
+
+// lox has no threads or function frames yet, so there is exactly one of each
+const THREAD_ID: i64 = 1;
+const FRAME_ID: i64 = 1;
+
+pub enum Flow {
+    Wait,
+    Resume,
+}
+
+// import depth first, then scope depth, so any statement of an imported module
+// is deeper than the import statement itself
+type Depth = (usize, usize);
+
+enum StepMode {
+    Continue,
+    Entry,
+    StepIn,
+    Next(Depth),
+    StepOut(Depth),
+}
+
+pub struct Session {
+    connection: Rc<Connection>,
+    program: Option<String>,
+    capabilities: HashSet<Capability>,
+    // keyed by canonical path, the same form the interpreter reports
+    breakpoints: HashMap<PathBuf, HashSet<usize>>,
+    step: StepMode,
+    paused: Option<Paused>,
+}
+
+// where the program stopped, the source is missing when the script path is unknown
+struct Paused {
+    source: Option<PathBuf>,
+    depth: Depth,
+    line: usize,
+    environment: Rc<Environment>,
+}
+
+impl Session {
+    pub fn new(connection: Rc<Connection>) -> Self {
+        Self {
+            connection,
+            program: None,
+            capabilities: HashSet::from([Capability::Core]),
+            breakpoints: HashMap::new(),
+            step: StepMode::Continue,
+            paused: None,
+        }
+    }
+
+    pub fn program(&self) -> Option<&str> {
+        self.program.as_deref()
+    }
+
+    pub fn capabilities(&self) -> &HashSet<Capability> {
+        &self.capabilities
+    }
+
+    pub fn handle_request(&mut self, request: &Json) -> Flow {
+        let arguments = &request["arguments"];
+
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.connection
+                    .send_response(request, json!({ "supportsConfigurationDoneRequest": true }));
+                self.connection.send_event("initialized", json!({}));
+            }
+            "launch" => {
+                // same as `--allow` cli flags, e.g. `"allow": ["io.write", "time"]`
+                let allowed: Result<HashSet<Capability>, String> = arguments["allow"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|entry| {
+                        entry
+                            .as_str()
+                            .and_then(Capability::from_name)
+                            .ok_or_else(|| {
+                                let name = entry
+                                    .as_str()
+                                    .map_or_else(|| entry.to_string(), str::to_owned);
+                                format!("Unknown capability '{}'.", name)
+                            })
+                    })
+                    .collect();
+                let allowed = match allowed {
+                    Ok(allowed) => allowed,
+                    Err(message) => {
+                        self.connection.send_error_response(request, &message);
+                        return Flow::Wait;
+                    }
+                };
+
+                match arguments["program"].as_str() {
+                    // stdin carries the protocol, so the script can't read from it
+                    _ if allowed.contains(&Capability::IoRead) => {
+                        self.connection.send_error_response(
+                            request,
+                            "Capability 'io.read' is not available while debugging, stdin is used by the debug protocol.",
+                        )
+                    }
+                    Some(program) => {
+                        self.program = Some(program.to_owned());
+                        if arguments["stopOnEntry"].as_bool() == Some(true) {
+                            self.step = StepMode::Entry;
+                        }
+                        self.capabilities.extend(allowed);
+                        self.connection.send_response(request, json!({}));
+                    }
+                    None => self
+                        .connection
+                        .send_error_response(request, "Missing 'program' launch argument."),
+                }
+            }
+            "setBreakpoints" => {
+                let path = normalize_path(arguments["source"]["path"].as_str().unwrap_or_default());
+                let lines: Vec<usize> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .map(|line| line as usize)
+                    .collect();
+
+                let statement_lines = statement_lines(&path);
+                let verified: Vec<Json> = lines
+                    .iter()
+                    .map(|line| json!({ "verified": statement_lines.contains(line), "line": line }))
+                    .collect();
+                self.breakpoints.insert(path, lines.into_iter().collect());
+                self.connection
+                    .send_response(request, json!({ "breakpoints": verified }));
+            }
+            "configurationDone" => {
+                self.connection.send_response(request, json!({}));
+                return Flow::Resume;
+            }
+            "threads" => self.connection.send_response(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "stackTrace" => {
+                let frames: Vec<Json> = self
+                    .paused
+                    .iter()
+                    .map(|paused| {
+                        json!({
+                            "id": FRAME_ID,
+                            "name": "<script>",
+                            "line": paused.line,
+                            "column": 1,
+                            "source": self.source(paused.source.as_deref()),
+                        })
+                    })
+                    .collect();
+                self.connection.send_response(
+                    request,
+                    json!({ "totalFrames": frames.len(), "stackFrames": frames }),
+                );
+            }
+            "scopes" => {
+                let chain = self.scope_chain();
+                let scopes: Vec<Json> = (0..chain.len())
+                    .map(|index| {
+                        let name = match chain.len() - index {
+                            1 => "Builtins",
+                            2 => "Globals",
+                            _ => "Block",
+                        };
+                        json!({
+                            "name": name,
+                            "variablesReference": index + 1,
+                            "expensive": false,
+                        })
+                    })
+                    .collect();
+                self.connection
+                    .send_response(request, json!({ "scopes": scopes }));
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or(0) as usize;
+                let variables: Vec<Json> = reference
+                    .checked_sub(1)
+                    .and_then(|index| self.scope_chain().get(index).cloned())
+                    .map(|environment| environment.bindings())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|(name, value)| {
+                        json!({
+                            "name": name,
+                            "value": value.inspect(),
+                            "variablesReference": 0,
+                        })
+                    })
+                    .collect();
+                self.connection
+                    .send_response(request, json!({ "variables": variables }));
+            }
+            "continue" => {
+                self.step = StepMode::Continue;
+                self.connection
+                    .send_response(request, json!({ "allThreadsContinued": true }));
+                return Flow::Resume;
+            }
+            "next" => {
+                self.step = StepMode::Next(self.paused_depth());
+                self.connection.send_response(request, json!({}));
+                return Flow::Resume;
+            }
+            "stepIn" => {
+                self.step = StepMode::StepIn;
+                self.connection.send_response(request, json!({}));
+                return Flow::Resume;
+            }
+            "stepOut" => {
+                self.step = StepMode::StepOut(self.paused_depth());
+                self.connection.send_response(request, json!({}));
+                return Flow::Resume;
+            }
+            "disconnect" | "terminate" => {
+                self.connection.send_response(request, json!({}));
+                process::exit(0);
+            }
+            command => self
+                .connection
+                .send_error_response(request, &format!("Unsupported request '{command}'.")),
+        }
+
+        Flow::Wait
+    }
+
+    fn pause(
+        &mut self,
+        reason: &str,
+        source: Option<&Path>,
+        depth: Depth,
+        line: usize,
+        environment: &Rc<Environment>,
+    ) {
+        self.paused = Some(Paused {
+            source: source.map(Path::to_path_buf),
+            depth,
+            line,
+            environment: Rc::clone(environment),
+        });
+        self.connection.send_event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        loop {
+            match self.connection.read_message() {
+                Some(request) => {
+                    if let Flow::Resume = self.handle_request(&request) {
+                        break;
+                    }
+                }
+                // client is gone, nobody is able to resume the program
+                None => process::exit(0),
+            }
+        }
+
+        self.paused = None;
+    }
+
+    fn has_breakpoint(&self, source: Option<&Path>, line: usize) -> bool {
+        source
+            .and_then(|source| self.breakpoints.get(source))
+            .is_some_and(|lines| lines.contains(&line))
+    }
+
+    fn source(&self, source: Option<&Path>) -> Json {
+        let path = source
+            .map(|source| source.to_string_lossy().into_owned())
+            .or_else(|| self.program.clone())
+            .unwrap_or_default();
+        let name = Path::new(&path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        json!({ "name": name, "path": path })
+    }
+
+    // innermost scope first, globals last
+    fn scope_chain(&self) -> Vec<Rc<Environment>> {
+        let mut chain = Vec::new();
+        let mut current = self
+            .paused
+            .as_ref()
+            .map(|paused| Rc::clone(&paused.environment));
+        while let Some(environment) = current {
+            current = environment.enclosing().cloned();
+            chain.push(environment);
+        }
+        chain
+    }
+
+    fn paused_depth(&self) -> Depth {
+        self.paused.as_ref().map_or((0, 0), |paused| paused.depth)
+    }
+}
+
+impl DebugHook for Session {
+    fn on_statement(
+        &mut self,
+        script_path: Option<&Path>,
+        import_depth: usize,
+        line: usize,
+        environment: &Rc<Environment>,
+    ) {
+        let depth = (import_depth, scope_depth(environment));
+
+        let reason = if self.has_breakpoint(script_path, line) {
+            Some("breakpoint")
+        } else {
+            match self.step {
+                StepMode::Continue => None,
+                StepMode::Entry => Some("entry"),
+                StepMode::StepIn => Some("step"),
+                StepMode::Next(from) if depth <= from => Some("step"),
+                StepMode::StepOut(from) if depth < from => Some("step"),
+                StepMode::Next(_) | StepMode::StepOut(_) => None,
+            }
+        };
+
+        if let Some(reason) = reason {
+            self.pause(reason, script_path, depth, line, environment);
+        }
+    }
+
+    fn on_output(&mut self, text: &str) {
+        self.connection.send_output("stdout", text);
+    }
+}
+
+fn scope_depth(environment: &Environment) -> usize {
+    let mut depth = 1;
+    let mut current = environment.enclosing();
+    while let Some(enclosing) = current {
+        depth += 1;
+        current = enclosing.enclosing();
+    }
+    depth
+}
+
+// lines a breakpoint can stop on, the ones holding a statement that reports its line
+fn statement_lines(path: &Path) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+    if let Ok(source) = fs::read_to_string(path) {
+        let tokens = Scanner::new(&source).scan_tokens();
+        collect_lines(&Parser::new(tokens).parse(), &mut lines);
+    }
+    lines
+}
+
+fn collect_lines(statements: &[Stmt], lines: &mut HashSet<usize>) {
+    for statement in statements {
+        lines.extend(statement.line());
+        match statement {
+            Stmt::Block(body) => collect_lines(body, lines),
+            Stmt::ForIn { body, .. } => collect_lines(std::slice::from_ref(body.as_ref()), lines),
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                collect_lines(body, lines);
+                if let Some((_, handler)) = catch {
+                    collect_lines(handler, lines);
+                }
+                if let Some(finally) = finally {
+                    collect_lines(finally, lines);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn normalize_path(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
@@ -11,7 +11,7 @@ pub fn print_statements(statements: &[Stmt], indent: usize) {
             Stmt::Expression(expr) => {
                 println!("{}Expression: {}", gen_indent(indent), expr.stringify())
             }
-            Stmt::Print(expr) => println!("{}Print: {}", gen_indent(indent), expr.stringify()),
+            Stmt::Print(_, expr) => println!("{}Print: {}", gen_indent(indent), expr.stringify()),
             Stmt::Var(token, value) => println!(
                 "{}Var: {} = {}",
                 gen_indent(indent),
@@ -22,7 +22,81 @@ pub fn print_statements(statements: &[Stmt], indent: usize) {
                     "nil".to_owned()
                 },
             ),
+            Stmt::VarDestructure(target, value) => println!(
+                "{}Var: {} = {}",
+                gen_indent(indent),
+                target.stringify(),
+                value.stringify()
+            ),
+            Stmt::Enum(name, variants) => println!(
+                "{}Enum: {} {{ {} }}",
+                gen_indent(indent),
+                name.lexeme,
+                variants
+                    .iter()
+                    .map(|(variant, fields)| if fields.is_empty() {
+                        variant.lexeme.to_owned()
+                    } else {
+                        let fields: Vec<&str> =
+                            fields.iter().map(|field| field.lexeme.as_str()).collect();
+                        format!("{}({})", variant.lexeme, fields.join(", "))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Stmt::Const(token, value) => println!(
+                "{}Const: {} = {}",
+                gen_indent(indent),
+                token.lexeme,
+                value.stringify()
+            ),
             Stmt::Block(statements) => print_statements(statements, indent + 1),
+            Stmt::Throw(_, expr) => println!("{}Throw: {}", gen_indent(indent), expr.stringify()),
+            Stmt::ForIn {
+                name,
+                iterable,
+                body,
+                ..
+            } => {
+                println!(
+                    "{}For {} in {}:",
+                    gen_indent(indent),
+                    name.lexeme,
+                    iterable.stringify()
+                );
+                print_statements(std::slice::from_ref(body.as_ref()), indent + 1);
+            }
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                println!("{}Try:", gen_indent(indent));
+                print_statements(body, indent + 1);
+                if let Some((name, handler)) = catch {
+                    println!("{}Catch ({}):", gen_indent(indent), name.lexeme);
+                    print_statements(handler, indent + 1);
+                }
+                if let Some(finally) = finally {
+                    println!("{}Finally:", gen_indent(indent));
+                    print_statements(finally, indent + 1);
+                }
+            }
+            Stmt::Break(_) => println!("{}Break", gen_indent(indent)),
+            Stmt::Continue(_) => println!("{}Continue", gen_indent(indent)),
+            Stmt::Import { path, names, .. } => match names {
+                Some(names) => println!(
+                    "{}Import: {{{}}} from \"{}\"",
+                    gen_indent(indent),
+                    names
+                        .iter()
+                        .map(|name| name.lexeme.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    path
+                ),
+                None => println!("{}Import: \"{}\"", gen_indent(indent), path),
+            },
         }
     }
 }
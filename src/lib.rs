@@ -1,15 +1,20 @@
 pub mod core;
+pub mod dap;
 pub mod helpers;
 pub use core::*;
+pub use dap::run_dap;
 
+use builtins::Capability;
 use interpreter::Interpreter;
 use parser::Parser;
 use reporter::console_reporter::ConsoleReporter;
 use scanner::Scanner;
 use std::{
+    collections::HashSet,
     env::args,
     fs,
     io::{self, Write},
+    path::Path,
     process,
     rc::Rc,
 };
@@ -19,7 +24,7 @@ pub fn run_file(path: &str) {
         println!("Running file: {path}");
     }
     let contents = fs::read_to_string(path).expect("Error reading a file");
-    run(&contents);
+    run_script(&contents, Some(Path::new(path)));
 }
 
 pub fn run_prompt() {
@@ -43,6 +48,11 @@ pub fn run_prompt() {
 }
 
 pub fn run(source: &str) {
+    run_script(source, None);
+}
+
+// imports are resolved relative to script_path, or to working directory without it
+pub fn run_script(source: &str, script_path: Option<&Path>) {
     let debug_run = is_debug_run();
     let log_reporter = ConsoleReporter::new();
 
@@ -68,8 +78,15 @@ pub fn run(source: &str) {
         process::exit(65);
     }
 
-    let mut interpreter = Interpreter::new().attach_reporter(Rc::clone(&log_reporter));
-    interpreter.interpret(&statements);
+    let mut interpreter = Interpreter::new()
+        .attach_reporter(Rc::clone(&log_reporter))
+        .attach_capabilities(allowed_capabilities());
+    if let Some(script_path) = script_path {
+        interpreter = interpreter.attach_script_path(script_path);
+    }
+    if let Some(code) = interpreter.interpret(&statements) {
+        process::exit(code);
+    }
 
     if log_reporter.is_had_runtime_error() {
         process::exit(70)
@@ -79,3 +96,26 @@ pub fn run(source: &str) {
 fn is_debug_run() -> bool {
     args().any(|arg| arg == "--debug" || arg == "-d")
 }
+
+// `--allow <capability>` may be repeated, core builtins are always allowed
+fn allowed_capabilities() -> HashSet<Capability> {
+    let mut capabilities = HashSet::from([Capability::Core]);
+    let args: Vec<String> = args().collect();
+
+    for pair in args.windows(2) {
+        if pair[0] != "--allow" {
+            continue;
+        }
+        match Capability::from_name(&pair[1]) {
+            Some(capability) => {
+                capabilities.insert(capability);
+            }
+            None => {
+                eprintln!("Unknown capability '{}'.", pair[1]);
+                process::exit(64);
+            }
+        }
+    }
+
+    capabilities
+}
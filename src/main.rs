@@ -1,17 +1,26 @@
-use std::cmp::Ordering;
 use std::env;
 
+use rlox::run_dap;
 use rlox::run_file;
 use rlox::run_prompt;
 
 fn main() {
-    let args_amount = env::args().count() - 1;
+    match script_argument() {
+        Some(argument) if argument == "dap" => run_dap(),
+        Some(script_path) => run_file(&script_path),
+        None => run_prompt(),
+    }
+}
 
-    match args_amount.cmp(&1) {
-        Ordering::Greater | Ordering::Equal => {
-            let script_path = env::args().nth(1).expect("First argument must exist");
-            run_file(&script_path);
+// first argument that is neither a flag nor a value of `--allow`
+fn script_argument() -> Option<String> {
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--allow" {
+            args.next();
+        } else if !arg.starts_with('-') {
+            return Some(arg);
         }
-        Ordering::Less => run_prompt(),
     }
+    None
 }